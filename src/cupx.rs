@@ -0,0 +1,112 @@
+use crate::{CupFile, Error, ParseIssue, Warning};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+const PICTURES_DIR: &str = "pics/";
+
+/// Pictures embedded in a `.cupx` archive
+///
+/// Pictures are looked up by the file names referenced in
+/// [`Waypoint::pictures`](crate::Waypoint::pictures). The lookup ignores the
+/// `pics/` folder prefix and is case-insensitive.
+pub struct PictureArchive<R = BufReader<File>> {
+    archive: ZipArchive<R>,
+    /// Maps lowercased picture names to their entry names in the archive
+    pictures: BTreeMap<String, String>,
+}
+
+impl<R> fmt::Debug for PictureArchive<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PictureArchive")
+            .field("pictures", &self.pictures.values())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: Read + Seek> PictureArchive<R> {
+    fn new(archive: ZipArchive<R>) -> Self {
+        let pictures = archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .filter_map(|name| {
+                let prefix = name.get(..PICTURES_DIR.len())?;
+                if !prefix.eq_ignore_ascii_case(PICTURES_DIR) {
+                    return None;
+                }
+                let picture = &name[PICTURES_DIR.len()..];
+                Some((picture.to_lowercase(), name.to_string()))
+            })
+            .collect();
+
+        Self { archive, pictures }
+    }
+
+    /// Returns the bytes of the picture with the given file name, or `None`
+    /// if the archive doesn't contain it.
+    pub fn picture(&mut self, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        let Some(entry_name) = self.pictures.get(&name.to_lowercase()) else {
+            return Ok(None);
+        };
+
+        // The size in the archive can't be trusted for preallocating
+        let mut entry = self.archive.by_name(entry_name)?;
+        let mut buf = Vec::new();
+        entry.read_to_end(&mut buf)?;
+        Ok(Some(buf))
+    }
+
+    /// Returns `true` if the archive contains a picture with the given file name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.pictures.contains_key(&name.to_lowercase())
+    }
+
+    /// Returns the names of all pictures in the archive, without the `pics/` prefix.
+    pub fn picture_names(&self) -> impl Iterator<Item = &str> {
+        self.pictures
+            .values()
+            .map(|name| &name[PICTURES_DIR.len()..])
+    }
+
+    /// Returns the picture names referenced by the waypoints of `cup_file`,
+    /// including the inline waypoints of its tasks, that are not contained in
+    /// the archive, in order of appearance.
+    pub fn missing_pictures<'a>(&self, cup_file: &'a CupFile) -> Vec<&'a str> {
+        let mut missing: Vec<&str> = Vec::new();
+        for name in cup_file.all_waypoints().flat_map(|wp| &wp.pictures) {
+            if !self.contains(name) && !missing.contains(&name.as_str()) {
+                missing.push(name);
+            }
+        }
+        missing
+    }
+}
+
+pub(crate) fn read_cupx<R: Read + Seek>(
+    reader: R,
+) -> Result<(CupFile, PictureArchive<R>, Vec<Warning>), Error> {
+    let mut archive = ZipArchive::new(reader)?;
+
+    let mut cup_entries = archive
+        .file_names()
+        .filter(|name| {
+            Path::new(name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("cup"))
+        })
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    let cup_entry = match cup_entries.len() {
+        1 => cup_entries.remove(0),
+        0 => return Err(ParseIssue::new("No .cup file found in archive").into()),
+        _ => return Err(ParseIssue::new("Multiple .cup files found in archive").into()),
+    };
+
+    let (cup_file, warnings) = CupFile::from_reader(archive.by_name(&cup_entry)?)?;
+
+    Ok((cup_file, PictureArchive::new(archive), warnings))
+}
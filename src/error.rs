@@ -12,8 +12,44 @@ pub enum Error {
     #[error("Encoding error: {0}")]
     Encoding(String),
 
+    #[error("Reference error: {0}")]
+    Reference(String),
+
     #[error(transparent)]
     Csv(#[from] csv::Error),
+
+    #[cfg(feature = "cupx")]
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+}
+
+impl Error {
+    /// Returns `true` for problems with the contents of a single file, after
+    /// which other files can still be processed, and `false` for I/O errors
+    ///
+    /// [`Error::Encoding`] is recoverable, since it is caused by the content
+    /// of the file, e.g. with [`DecodeErrorPolicy::Fail`](crate::DecodeErrorPolicy::Fail),
+    /// or by characters that can't be written in the target encoding.
+    /// [`Error::Reference`] is recoverable, since it leaves the
+    /// [`CupFile`](crate::CupFile) unchanged.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Error::Io(_) => false,
+            Error::Parse(_) | Error::Encoding(_) | Error::Reference(_) => true,
+            Error::Csv(error) => !error.is_io_error(),
+            #[cfg(feature = "cupx")]
+            Error::Zip(error) => !matches!(error, zip::result::ZipError::Io(_)),
+        }
+    }
+
+    /// Returns the line the error occurred on, if known
+    pub fn line(&self) -> Option<u64> {
+        match self {
+            Error::Parse(issue) => issue.line(),
+            Error::Csv(error) => error.position().map(|p| p.line()),
+            _ => None,
+        }
+    }
 }
 
 impl From<ParseIssue> for Error {
@@ -23,15 +59,19 @@ impl From<ParseIssue> for Error {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Warning(ParseIssue);
+pub struct Warning(pub(crate) ParseIssue);
 
 impl Warning {
     pub fn message(&self) -> &str {
-        &self.0.message
+        self.0.message()
     }
 
     pub fn line(&self) -> Option<u64> {
-        self.0.line
+        self.0.line()
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.0.severity()
     }
 }
 
@@ -41,22 +81,64 @@ impl From<ParseIssue> for Warning {
     }
 }
 
+impl From<Warning> for ParseIssue {
+    fn from(warning: Warning) -> Self {
+        warning.0
+    }
+}
+
+/// How much of the input was lost due to a [`ParseIssue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Nothing was lost, e.g. a notice about how the input was interpreted
+    Info,
+    /// A single field was ignored, e.g. an invalid runway direction
+    Warning,
+    /// A whole record was skipped, e.g. a waypoint with invalid coordinates
+    Error,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParseIssue {
     message: String,
     line: Option<u64>,
+    severity: Severity,
 }
 
 impl ParseIssue {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn line(&self) -> Option<u64> {
+        self.line
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
     pub(crate) fn new(message: impl Into<String>) -> Self {
         let message = message.into();
         let line = None;
-        Self { message, line }
+        let severity = Severity::Warning;
+        Self {
+            message,
+            line,
+            severity,
+        }
     }
 
     pub(crate) fn with_record(self, record: &StringRecord) -> Self {
-        let message = self.message;
         let line = record.position().map(|p| p.line());
-        Self { message, line }
+        Self { line, ..self }
+    }
+
+    pub(crate) fn with_line(self, line: Option<u64>) -> Self {
+        Self { line, ..self }
+    }
+
+    pub(crate) fn with_severity(self, severity: Severity) -> Self {
+        Self { severity, ..self }
     }
 }
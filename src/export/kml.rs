@@ -0,0 +1,115 @@
+use crate::{CupFile, Error, Task, Waypoint, WaypointStyle};
+use std::fmt::Write;
+
+/// Formats the waypoints and tasks of `cup_file` as a KML document
+///
+/// Waypoints are grouped into one folder per style, in order of first
+/// appearance. Tasks with points that can't be resolved are omitted.
+pub fn to_kml(cup_file: &CupFile) -> Result<String, Error> {
+    let mut output = String::new();
+    // Writing into a `String` can't fail
+    write_document(&mut output, cup_file).expect("formatting KML failed");
+    Ok(output)
+}
+
+fn write_document(output: &mut String, cup_file: &CupFile) -> std::fmt::Result {
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n");
+    output.push_str("<Document>\n");
+
+    for (style, waypoints) in group_by_style(&cup_file.waypoints) {
+        writeln!(output, "<Folder>")?;
+        writeln!(output, "<name>{}</name>", escape(style.as_str()))?;
+        for waypoint in waypoints {
+            write_waypoint(output, waypoint)?;
+        }
+        writeln!(output, "</Folder>")?;
+    }
+
+    let tasks = cup_file
+        .tasks
+        .iter()
+        .filter_map(|task| Some((task, cup_file.resolve_task(task).ok()?)))
+        .collect::<Vec<_>>();
+
+    if !tasks.is_empty() {
+        writeln!(output, "<Folder>")?;
+        writeln!(output, "<name>Tasks</name>")?;
+        for (task, waypoints) in tasks {
+            write_task(output, task, &waypoints)?;
+        }
+        writeln!(output, "</Folder>")?;
+    }
+
+    output.push_str("</Document>\n");
+    output.push_str("</kml>\n");
+
+    Ok(())
+}
+
+fn group_by_style(waypoints: &[Waypoint]) -> Vec<(WaypointStyle, Vec<&Waypoint>)> {
+    let mut groups: Vec<(WaypointStyle, Vec<&Waypoint>)> = Vec::new();
+    for waypoint in waypoints {
+        match groups
+            .iter_mut()
+            .find(|(style, _)| *style == waypoint.style)
+        {
+            Some((_, group)) => group.push(waypoint),
+            None => groups.push((waypoint.style, vec![waypoint])),
+        }
+    }
+    groups
+}
+
+fn write_waypoint(output: &mut String, waypoint: &Waypoint) -> std::fmt::Result {
+    writeln!(output, "<Placemark>")?;
+    writeln!(output, "<name>{}</name>", escape(&waypoint.name))?;
+    if !waypoint.description.is_empty() {
+        let description = escape(&waypoint.description);
+        writeln!(output, "<description>{description}</description>")?;
+    }
+    writeln!(output, "<Point>")?;
+    writeln!(
+        output,
+        "<coordinates>{}</coordinates>",
+        coordinates(waypoint)
+    )?;
+    writeln!(output, "</Point>")?;
+    writeln!(output, "</Placemark>")
+}
+
+fn write_task(output: &mut String, task: &Task, waypoints: &[&Waypoint]) -> std::fmt::Result {
+    let name = task.description.as_deref().unwrap_or_default();
+
+    writeln!(output, "<Placemark>")?;
+    writeln!(output, "<name>{}</name>", escape(name))?;
+    writeln!(output, "<LineString>")?;
+    writeln!(output, "<tessellate>1</tessellate>")?;
+    writeln!(output, "<coordinates>")?;
+    for waypoint in waypoints {
+        writeln!(output, "{}", coordinates(waypoint))?;
+    }
+    writeln!(output, "</coordinates>")?;
+    writeln!(output, "</LineString>")?;
+    writeln!(output, "</Placemark>")
+}
+
+fn coordinates(waypoint: &Waypoint) -> String {
+    let elevation = waypoint.elevation.to_meters();
+    format!("{},{},{}", waypoint.longitude, waypoint.latitude, elevation)
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,4 @@
+#[cfg(feature = "kml")]
+pub mod kml;
+#[cfg(feature = "openair")]
+pub mod openair;
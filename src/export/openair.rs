@@ -0,0 +1,169 @@
+use crate::geo::destination_point;
+use crate::{CupFile, Error, ObsZoneStyle, ObservationZone, Task, Waypoint};
+use std::fmt::Write;
+
+const METERS_PER_NAUTICAL_MILE: f64 = 1852.0;
+
+/// Formats the observation zones of `task` as OpenAir airspaces
+///
+/// Cylinders become `DC` circles, sectors become `DB` arcs closed at the
+/// task point, and lines become two `DP` points. Zones without a radius
+/// are omitted.
+pub fn to_openair(task: &Task, cup_file: &CupFile) -> Result<String, Error> {
+    let waypoints = cup_file.resolve_task(task)?;
+
+    let mut output = String::new();
+    if let Some(description) = &task.description {
+        // Writing into a `String` can't fail
+        writeln!(output, "* {description}").expect("formatting OpenAir failed");
+    }
+
+    for zone in &task.observation_zones {
+        let index = zone.index as usize;
+        if index >= waypoints.len() {
+            let message = format!("Observation zone {index} has no matching task point");
+            return Err(Error::Reference(message));
+        }
+
+        write_zone(&mut output, zone, &waypoints, index).expect("formatting OpenAir failed");
+    }
+
+    Ok(output)
+}
+
+fn write_zone(
+    output: &mut String,
+    zone: &ObservationZone,
+    waypoints: &[&Waypoint],
+    index: usize,
+) -> std::fmt::Result {
+    let Some(r1) = &zone.r1 else {
+        return Ok(());
+    };
+
+    let waypoint = waypoints[index];
+    let direction = zone_direction(zone, waypoints, index);
+
+    write_header(output, waypoint)?;
+    if zone.is_line() {
+        write_line(output, waypoint, direction, r1.to_meters())?;
+    } else {
+        let angle = zone.a1.map_or(180.0, f64::from);
+        write_area(output, waypoint, direction, r1.to_meters(), angle)?;
+    }
+
+    let r2 = zone.r2.as_ref().map(|r2| r2.to_meters());
+    if let Some(r2) = r2.filter(|r2| *r2 > 0.0) {
+        write_header(output, waypoint)?;
+        let angle = zone.a2.map_or(180.0, f64::from);
+        write_area(output, waypoint, direction, r2, angle)?;
+    }
+
+    Ok(())
+}
+
+fn write_header(output: &mut String, waypoint: &Waypoint) -> std::fmt::Result {
+    writeln!(output, "AC OTHER")?;
+    writeln!(output, "AN {}", waypoint.name)?;
+    writeln!(output, "AL GND")?;
+    writeln!(output, "AH UNL")
+}
+
+/// Writes a circle for angles of 180 degrees or more, and a sector
+/// spanning `angle` degrees to each side of `direction` otherwise
+fn write_area(
+    output: &mut String,
+    waypoint: &Waypoint,
+    direction: f64,
+    radius: f64,
+    angle: f64,
+) -> std::fmt::Result {
+    let center = coordinate(waypoint.latitude, waypoint.longitude);
+    if angle >= 180.0 {
+        writeln!(output, "V X={center}")?;
+        return writeln!(output, "DC {:.3}", radius / METERS_PER_NAUTICAL_MILE);
+    }
+
+    let (lat, lon) = (waypoint.latitude, waypoint.longitude);
+    let (start_lat, start_lon) = destination_point(lat, lon, direction - angle, radius);
+    let (end_lat, end_lon) = destination_point(lat, lon, direction + angle, radius);
+
+    writeln!(output, "DP {center}")?;
+    writeln!(output, "V X={center}")?;
+    writeln!(output, "V D=+")?;
+    writeln!(
+        output,
+        "DB {}, {}",
+        coordinate(start_lat, start_lon),
+        coordinate(end_lat, end_lon)
+    )
+}
+
+/// Writes a line extending `half_width` to each side of the task point,
+/// perpendicular to `direction`
+fn write_line(
+    output: &mut String,
+    waypoint: &Waypoint,
+    direction: f64,
+    half_width: f64,
+) -> std::fmt::Result {
+    let (lat, lon) = (waypoint.latitude, waypoint.longitude);
+    for bearing in [direction - 90.0, direction + 90.0] {
+        let (lat, lon) = destination_point(lat, lon, bearing, half_width);
+        writeln!(output, "DP {}", coordinate(lat, lon))?;
+    }
+    Ok(())
+}
+
+/// Returns the bearing in degrees `[0, 360)` from the task point towards the
+/// center of its zone, which points away from the course
+fn zone_direction(zone: &ObservationZone, waypoints: &[&Waypoint], index: usize) -> f64 {
+    let waypoint = waypoints[index];
+    let bearing_to = |idx: usize| waypoint.bearing_to(waypoints[idx]);
+    let away = |bearing: f64| (bearing + 180.0) % 360.0;
+
+    let to_previous = index.checked_sub(1).map(bearing_to);
+    let to_next = (index + 1 < waypoints.len()).then(|| bearing_to(index + 1));
+    let to_start = (index > 0).then(|| bearing_to(0));
+
+    let direction = match zone.style {
+        ObsZoneStyle::Fixed => zone.a12.map(f64::from),
+        ObsZoneStyle::Symmetrical => match (to_previous, to_next) {
+            (Some(previous), Some(next)) => Some(outer_bisector(previous, next)),
+            (previous, next) => previous.or(next).map(away),
+        },
+        ObsZoneStyle::ToNextPoint => to_next.or(to_previous).map(away),
+        ObsZoneStyle::ToPreviousPoint => to_previous.or(to_next).map(away),
+        ObsZoneStyle::ToStartPoint => to_start.or(to_next).map(away),
+    };
+
+    direction.unwrap_or_default().rem_euclid(360.0)
+}
+
+/// Returns the bisector of the larger angle between the two bearings
+fn outer_bisector(a: f64, b: f64) -> f64 {
+    let diff = (b - a).rem_euclid(360.0);
+    let bisector = a + diff / 2.0;
+    if diff < 180.0 {
+        bisector + 180.0
+    } else {
+        bisector
+    }
+}
+
+/// Formats a coordinate as `DD:MM:SS N DDD:MM:SS E`
+fn coordinate(lat: f64, lon: f64) -> String {
+    let lat_hemisphere = if lat >= 0.0 { 'N' } else { 'S' };
+    let lon_hemisphere = if lon >= 0.0 { 'E' } else { 'W' };
+    format!(
+        "{} {lat_hemisphere} {} {lon_hemisphere}",
+        dms(lat, 2),
+        dms(lon, 3)
+    )
+}
+
+fn dms(value: f64, degree_digits: usize) -> String {
+    let seconds = (value.abs() * 3600.0).round() as u32;
+    let (degrees, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    format!("{degrees:0degree_digits$}:{minutes:02}:{seconds:02}")
+}
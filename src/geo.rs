@@ -0,0 +1,120 @@
+//! Spherical geometry helpers operating on decimal degree coordinates
+
+/// Mean earth radius in meters (IUGG)
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Returns the great-circle distance in meters between two coordinates
+/// using the haversine formula.
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let lat1 = lat1.to_radians();
+    let lat2 = lat2.to_radians();
+    let delta_lat = lat2 - lat1;
+    let delta_lon = (lon2 - lon1).to_radians();
+
+    let a =
+        (delta_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Returns the initial great-circle bearing in degrees `[0, 360)` from the
+/// first to the second coordinate.
+pub fn initial_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let lat1 = lat1.to_radians();
+    let lat2 = lat2.to_radians();
+    let delta_lon = (lon2 - lon1).to_radians();
+
+    let y = delta_lon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lon.cos();
+
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Returns the coordinate reached by travelling `distance` meters from the
+/// given coordinate along the great circle with the initial `bearing` in degrees.
+#[cfg_attr(not(feature = "openair"), allow(dead_code))]
+pub fn destination_point(lat: f64, lon: f64, bearing: f64, distance: f64) -> (f64, f64) {
+    let lat = lat.to_radians();
+    let lon = lon.to_radians();
+    let bearing = bearing.to_radians();
+    let delta = distance / EARTH_RADIUS;
+
+    let lat2 = (lat.sin() * delta.cos() + lat.cos() * delta.sin() * bearing.cos()).asin();
+    let lon2 =
+        lon + (bearing.sin() * delta.sin() * lat.cos()).atan2(delta.cos() - lat.sin() * lat2.sin());
+
+    let lon2 = (lon2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+    (lat2.to_degrees(), lon2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONDON: (f64, f64) = (51.5074, -0.1278);
+    const PARIS: (f64, f64) = (48.8566, 2.3522);
+    const NEW_YORK: (f64, f64) = (40.7128, -74.0060);
+    const LOS_ANGELES: (f64, f64) = (34.0522, -118.2437);
+    const SYDNEY: (f64, f64) = (-33.8688, 151.2093);
+    const MELBOURNE: (f64, f64) = (-37.8136, 144.9631);
+
+    fn assert_relative_eq(actual: f64, expected: f64) {
+        let error = ((actual - expected) / expected).abs();
+        assert!(error < 0.001, "expected {expected}, got {actual}");
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        let cases = [
+            (LONDON, PARIS, 343_500.0),
+            (NEW_YORK, LOS_ANGELES, 3_935_700.0),
+            (SYDNEY, MELBOURNE, 713_400.0),
+        ];
+
+        for ((lat1, lon1), (lat2, lon2), expected) in cases {
+            assert_relative_eq(haversine_distance(lat1, lon1, lat2, lon2), expected);
+            assert_relative_eq(haversine_distance(lat2, lon2, lat1, lon1), expected);
+        }
+    }
+
+    #[test]
+    fn test_haversine_distance_same_point() {
+        let (lat, lon) = LONDON;
+        assert_eq!(haversine_distance(lat, lon, lat, lon), 0.0);
+    }
+
+    #[test]
+    fn test_initial_bearing() {
+        let cases = [
+            (LONDON, PARIS, 148.1),
+            (NEW_YORK, LOS_ANGELES, 273.7),
+            ((45.0, 10.0), (46.0, 10.0), 0.0),
+            ((46.0, 10.0), (45.0, 10.0), 180.0),
+        ];
+
+        for ((lat1, lon1), (lat2, lon2), expected) in cases {
+            let bearing = initial_bearing(lat1, lon1, lat2, lon2);
+            assert!(
+                (bearing - expected).abs() < 0.1,
+                "expected {expected}, got {bearing}"
+            );
+            assert!((0.0..360.0).contains(&bearing));
+        }
+    }
+
+    #[test]
+    fn test_destination_point() {
+        let (lat1, lon1) = LONDON;
+        let (lat2, lon2) = PARIS;
+        let distance = haversine_distance(lat1, lon1, lat2, lon2);
+        let bearing = initial_bearing(lat1, lon1, lat2, lon2);
+
+        let (lat, lon) = destination_point(lat1, lon1, bearing, distance);
+        assert!((lat - lat2).abs() < 1e-9, "expected {lat2}, got {lat}");
+        assert!((lon - lon2).abs() < 1e-9, "expected {lon2}, got {lon}");
+
+        let (lat, lon) = destination_point(0.0, 179.5, 90.0, 111_195.0);
+        assert!(lat.abs() < 1e-9);
+        assert!((lon - -179.5).abs() < 1e-3, "expected -179.5, got {lon}");
+    }
+}
@@ -1,16 +1,28 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "cupx")]
+mod cupx;
 mod error;
+#[cfg(any(feature = "kml", feature = "openair"))]
+mod export;
+mod geo;
+mod options;
 mod parser;
 pub mod spec;
 mod types;
 mod writer;
 
-pub use error::{Error, Warning};
+#[cfg(feature = "cupx")]
+pub use cupx::PictureArchive;
+pub use error::{Error, ParseIssue, Severity, Warning};
+pub use options::{DecodeErrorPolicy, LineEnding, ParseOptions, WriteOptions};
 pub use types::*;
 
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{BufRead, Read, Write};
 use std::path::Path;
 use std::str::FromStr;
 
@@ -21,6 +33,60 @@ pub enum Encoding {
     Utf8,
     /// Windows-1252 encoding (legacy)
     Windows1252,
+    /// ISO-8859-2 encoding (legacy, Central European)
+    Latin2,
+    /// Windows-1250 encoding (legacy, Central European)
+    Windows1250,
+}
+
+impl Encoding {
+    /// Guesses the encoding of `bytes` from their content
+    ///
+    /// Returns `None` if the most likely encoding is not supported by this
+    /// crate.
+    #[cfg(feature = "charset-detect")]
+    pub fn detect(bytes: &[u8]) -> Option<Encoding> {
+        parser::detect_encoding(bytes)
+    }
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Windows1252 => "windows-1252",
+            Encoding::Latin2 => "ISO-8859-2",
+            Encoding::Windows1250 => "windows-1250",
+        })
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    /// Parses a case-insensitive encoding label, e.g. `utf-8` or `cp1252`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "windows-1252" | "cp1252" | "latin1" => Ok(Encoding::Windows1252),
+            "iso-8859-2" | "latin2" => Ok(Encoding::Latin2),
+            "windows-1250" | "cp1250" => Ok(Encoding::Windows1250),
+            _ => Err(format!(
+                "Unknown encoding: '{s}' (expected utf-8, windows-1252, iso-8859-2 or windows-1250)"
+            )),
+        }
+    }
+}
+
+/// Handling of tasks referring to removed waypoints, see
+/// [`CupFile::filter_waypoints()`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TaskRetention {
+    /// Drop tasks referring to a removed waypoint by name
+    #[default]
+    DropReferencing,
+    /// Keep all tasks, even if some of their points can't be resolved anymore
+    KeepAll,
 }
 
 /// SeeYou CUP file representation
@@ -33,34 +99,144 @@ pub struct CupFile {
 }
 
 impl CupFile {
+    /// Creates an empty file with room for the given number of waypoints and
+    /// tasks
+    pub fn with_capacity(waypoints: usize, tasks: usize) -> Self {
+        Self {
+            waypoints: Vec::with_capacity(waypoints),
+            tasks: Vec::with_capacity(tasks),
+        }
+    }
+
+    /// Reads the whole input into memory and parses it
+    ///
+    /// The input is read in large chunks directly into a growing buffer, so
+    /// unbuffered readers like sockets don't need to be wrapped in a
+    /// [`BufReader`](std::io::BufReader). Besides the parsed file, parsing
+    /// temporarily needs memory for the raw input and, for legacy encodings,
+    /// its decoded copy. To parse large waypoint files with constant memory
+    /// use [`CupFile::waypoints_iter()`] instead, and for data that is
+    /// already in memory use [`CupFile::from_bytes()`].
     pub fn from_reader<R: Read>(reader: R) -> Result<(Self, Vec<Warning>), Error> {
-        parser::parse(reader, None)
+        Self::from_reader_with_options(reader, &ParseOptions::default())
     }
 
     pub fn from_reader_with_encoding<R: Read>(
         reader: R,
         encoding: Encoding,
     ) -> Result<(Self, Vec<Warning>), Error> {
-        parser::parse(reader, Some(encoding))
+        let options = ParseOptions {
+            encoding: Some(encoding),
+            ..Default::default()
+        };
+        Self::from_reader_with_options(reader, &options)
+    }
+
+    pub fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<Warning>), Error> {
+        parser::parse(reader, options)
+    }
+
+    /// Parses a file that is already in memory, e.g. the body of an HTTP
+    /// request, without copying it into an intermediate buffer
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, Vec<Warning>), Error> {
+        Self::from_bytes_with_options(bytes, &ParseOptions::default())
+    }
+
+    pub fn from_bytes_with_encoding(
+        bytes: &[u8],
+        encoding: Encoding,
+    ) -> Result<(Self, Vec<Warning>), Error> {
+        let options = ParseOptions {
+            encoding: Some(encoding),
+            ..Default::default()
+        };
+        Self::from_bytes_with_options(bytes, &options)
+    }
+
+    pub fn from_bytes_with_options(
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<Warning>), Error> {
+        parser::parse_bytes(bytes, options)
     }
 
+    /// Parses with [`ParseOptions::collect_errors`] enabled, returning all
+    /// warnings and recoverable errors in file order instead of failing on
+    /// the first error.
+    ///
+    /// Only I/O and encoding failures are returned as `Err`. If the header
+    /// row lacks a required column, the issue is reported and an empty file
+    /// is returned, since the remaining rows can't be interpreted.
+    pub fn from_reader_collect_errors<R: Read>(
+        reader: R,
+    ) -> Result<(Self, Vec<ParseIssue>), Error> {
+        let options = ParseOptions {
+            collect_errors: true,
+            ..Default::default()
+        };
+
+        let (cup_file, warnings) = Self::from_reader_with_options(reader, &options)?;
+        let issues = warnings.into_iter().map(ParseIssue::from).collect();
+        Ok((cup_file, issues))
+    }
+
+    /// Parses the waypoints one record at a time, without reading the whole
+    /// input into memory.
+    ///
+    /// The iterator stops at the task section. Invalid waypoints are returned
+    /// as errors, while field-level warnings are not reported. The encoding is
+    /// detected from the first non-ASCII character, falling back to
+    /// Windows-1252 if it isn't valid UTF-8.
+    pub fn waypoints_iter<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Waypoint, Error>> {
+        parser::WaypointsIter::new(reader)
+    }
+
+    /// Reads the file into memory and parses it, see [`CupFile::from_reader()`]
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<Warning>), Error> {
-        let file = File::open(path)?;
-        Self::from_reader(file)
+        Self::from_path_with_options(path, &ParseOptions::default())
     }
 
     pub fn from_path_with_encoding<P: AsRef<Path>>(
         path: P,
         encoding: Encoding,
     ) -> Result<(Self, Vec<Warning>), Error> {
+        let options = ParseOptions {
+            encoding: Some(encoding),
+            ..Default::default()
+        };
+        Self::from_path_with_options(path, &options)
+    }
+
+    pub fn from_path_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<Warning>), Error> {
+        // Allocates the buffer for the whole file upfront
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes_with_options(&bytes, options)
+    }
+
+    /// Reads a `.cupx` archive, which bundles a `.cup` file with the
+    /// pictures referenced by its waypoints.
+    ///
+    /// The archive must contain exactly one `.cup` entry. Pictures are
+    /// expected in the `pics/` folder and can be fetched from the returned
+    /// [`PictureArchive`].
+    #[cfg(feature = "cupx")]
+    pub fn from_cupx_path<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<(Self, PictureArchive, Vec<Warning>), Error> {
         let file = File::open(path)?;
-        Self::from_reader_with_encoding(file, encoding)
+        cupx::read_cupx(std::io::BufReader::new(file))
     }
 
     // The trait can't be implemented for `(Self, Vec<Warning>)`
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<(Self, Vec<Warning>), Error> {
-        Self::from_reader(s.as_bytes())
+        Self::from_bytes(s.as_bytes())
     }
 
     pub fn to_writer<W: Write>(&self, writer: W) -> Result<(), Error> {
@@ -72,25 +248,512 @@ impl CupFile {
         writer: W,
         encoding: Encoding,
     ) -> Result<(), Error> {
-        writer::write(self, writer, encoding)
+        let options = WriteOptions {
+            encoding,
+            ..Default::default()
+        };
+        self.to_writer_with_options(writer, &options)
+    }
+
+    pub fn to_writer_with_options<W: Write>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        writer::write(self, writer, options)
+    }
+
+    /// Writes a single waypoint row, e.g. to add waypoints to an existing
+    /// file without rewriting it
+    ///
+    /// The caller is responsible for the rest of the file: the header row
+    /// must have been written before, and the row has to be placed before the
+    /// task separator, if any. Extra columns of the waypoint are written in
+    /// their original order and must match the header.
+    pub fn append_waypoint_to_writer<W: Write>(
+        writer: &mut W,
+        waypoint: &Waypoint,
+        encoding: Encoding,
+    ) -> Result<(), Error> {
+        let options = WriteOptions {
+            encoding,
+            ..Default::default()
+        };
+        writer::write_single_waypoint(waypoint, writer, &options)
+    }
+
+    /// Writes the header row and waypoints without the task section, which
+    /// is a valid CUP file on its own
+    pub fn write_waypoints_only<W: Write>(
+        &self,
+        writer: W,
+        encoding: Encoding,
+    ) -> Result<(), Error> {
+        let options = WriteOptions {
+            encoding,
+            ..Default::default()
+        };
+        writer::write_waypoints(self, writer, &options)
+    }
+
+    /// Writes the task section, starting with the `-----Related Tasks-----`
+    /// separator, without the header row and waypoints
+    ///
+    /// The separator is written even if there are no tasks.
+    pub fn write_tasks_only<W: Write>(&self, writer: W, encoding: Encoding) -> Result<(), Error> {
+        let options = WriteOptions {
+            encoding,
+            ..Default::default()
+        };
+        writer::write_tasks(self, writer, &options)
     }
 
     pub fn to_path<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
         self.to_path_with_encoding(path, Encoding::Utf8)
     }
 
+    /// Writes the file to `path`, replacing any existing file
+    ///
+    /// The output is written to a temporary file next to `path` first, so
+    /// that an error, e.g. a character that can't be encoded, leaves no
+    /// partially written file behind.
     pub fn to_path_with_encoding<P: AsRef<Path>>(
         &self,
         path: P,
         encoding: Encoding,
     ) -> Result<(), Error> {
-        let file = File::create(path)?;
-        self.to_writer_with_encoding(file, encoding)
+        let path = path.as_ref();
+        let mut tmp_name = OsString::from(".");
+        tmp_name.push(path.file_name().unwrap_or_default());
+        tmp_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_name);
+
+        let result = File::create(&tmp_path)
+            .map_err(Error::from)
+            .and_then(|file| self.to_writer_with_encoding(file, encoding))
+            .and_then(|_| Ok(std::fs::rename(&tmp_path, path)?));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+
+        result
     }
 
-    pub fn to_string(&self) -> Result<String, Error> {
+    /// Returns the number of waypoints in the waypoint section
+    pub fn waypoint_count(&self) -> usize {
+        self.waypoints.len()
+    }
+
+    /// Returns the number of tasks
+    pub fn task_count(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns `true` if the file has neither waypoints nor tasks
+    pub fn is_empty(&self) -> bool {
+        self.waypoints.is_empty() && self.tasks.is_empty()
+    }
+
+    /// Returns the geographic extent of all waypoints, or `None` if there are
+    /// no waypoints.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_waypoints(&self.waypoints)
+    }
+
+    /// Returns a summary of the file, e.g. for a one-line overview
+    ///
+    /// Only the waypoints of the waypoint section are taken into account,
+    /// not the inline waypoints of the tasks.
+    pub fn statistics(&self) -> Statistics {
+        Statistics::from_cup_file(self)
+    }
+
+    /// Returns all waypoints of the file, including the inline waypoints of
+    /// the tasks.
+    ///
+    /// The waypoints of the waypoint section come first, followed by the
+    /// inline waypoints of each task, in task order.
+    pub fn all_waypoints(&self) -> impl Iterator<Item = &Waypoint> {
+        let inline_waypoints = self.tasks.iter().flat_map(|task| &task.points);
+        let inline_waypoints = inline_waypoints.map(|(_, waypoint)| waypoint);
+        self.waypoints.iter().chain(inline_waypoints)
+    }
+
+    /// Resolves the points of `task` to waypoints, in task order
+    ///
+    /// Inline `Point=` waypoints of the task take precedence over name
+    /// lookups in the waypoint section. Returns an [`Error::Reference`] for
+    /// the first task point that can't be resolved.
+    pub fn resolve_task<'a>(&'a self, task: &'a Task) -> Result<Vec<&'a Waypoint>, Error> {
+        let waypoints = task.waypoint_names.iter().enumerate();
+        let waypoints = waypoints.map(|(idx, name)| {
+            let inline = task.points.iter().find(|(i, _)| *i as usize == idx);
+            let inline = inline.map(|(_, waypoint)| waypoint);
+            let waypoint = inline.or_else(|| self.waypoints.iter().find(|wp| wp.name == *name));
+            waypoint
+                .ok_or_else(|| Error::Reference(format!("Unresolved task point {idx}: '{name}'")))
+        });
+
+        waypoints.collect()
+    }
+
+    /// Returns the `(latitude, longitude)` pairs of the points of `task`, in
+    /// task order, e.g. for drawing the task as a polyline
+    ///
+    /// Returns an error if any task point can't be resolved, see
+    /// [`CupFile::resolve_task()`].
+    pub fn task_polyline(&self, task: &Task) -> Result<Vec<(f64, f64)>, Error> {
+        let waypoints = self.resolve_task(task)?;
+        let points = waypoints.iter().map(|wp| (wp.latitude, wp.longitude));
+        Ok(points.collect())
+    }
+
+    /// Returns the distance and bearing of each leg of `task`
+    ///
+    /// Returns an error if any task point can't be resolved, see
+    /// [`CupFile::resolve_task()`].
+    pub fn task_legs(&self, task: &Task) -> Result<Vec<Leg>, Error> {
+        let waypoints = self.resolve_task(task)?;
+        let legs = waypoints.windows(2).enumerate();
+        let legs = legs.map(|(from, leg)| Leg {
+            from,
+            to: from + 1,
+            distance_m: leg[0].distance_to(leg[1]),
+            bearing_deg: leg[0].bearing_to(leg[1]),
+        });
+
+        Ok(legs.collect())
+    }
+
+    /// Returns the indices of the tasks referring to the waypoint `name`,
+    /// either as task point or as one of the [multiple starts](Task::multiple_starts)
+    ///
+    /// Task points defined by an inline `Point=` waypoint don't refer to the
+    /// waypoint section and are ignored, see [`CupFile::resolve_task()`].
+    pub fn tasks_referencing(&self, name: &str) -> Vec<usize> {
+        let tasks = self.tasks.iter().enumerate();
+        let tasks = tasks.filter(|(_, task)| refers_to(task, |task_point| task_point == name));
+        tasks.map(|(idx, _)| idx).collect()
+    }
+
+    /// Removes all waypoints named `name`
+    ///
+    /// Returns an [`Error::Reference`], leaving the file unchanged, if there
+    /// is no such waypoint or if any task still refers to it, see
+    /// [`CupFile::tasks_referencing()`].
+    pub fn remove_waypoint(&mut self, name: &str) -> Result<(), Error> {
+        if !self.waypoints.iter().any(|wp| wp.name == name) {
+            return Err(Error::Reference(format!("Unknown waypoint: '{name}'")));
+        }
+
+        let tasks = self.tasks_referencing(name);
+        if !tasks.is_empty() {
+            let tasks = tasks.iter().map(ToString::to_string);
+            let tasks = tasks.collect::<Vec<_>>().join(", ");
+            let message = format!("Waypoint '{name}' is referenced by tasks {tasks}");
+            return Err(Error::Reference(message));
+        }
+
+        self.waypoints.retain(|wp| wp.name != name);
+        Ok(())
+    }
+
+    /// Returns a copy of the file with only the waypoints matching `f`
+    ///
+    /// With [`TaskRetention::DropReferencing`], tasks referring to a removed
+    /// waypoint by name, as task point or as one of their
+    /// [multiple starts](Task::multiple_starts), are dropped, while tasks
+    /// using inline waypoints for those points are kept. With
+    /// [`TaskRetention::KeepAll`], all tasks are kept.
+    pub fn filter_waypoints<F: Fn(&Waypoint) -> bool>(
+        &self,
+        f: F,
+        retention: TaskRetention,
+    ) -> CupFile {
+        let (waypoints, dropped) = self.partition_waypoints(f);
+
+        let tasks = self.tasks.iter().enumerate();
+        let tasks =
+            tasks.filter(|(idx, _)| retention == TaskRetention::KeepAll || !dropped.contains(idx));
+
+        CupFile {
+            waypoints: waypoints.into_iter().cloned().collect(),
+            tasks: tasks.map(|(_, task)| task.clone()).collect(),
+        }
+    }
+
+    /// Splits the waypoints into those matching `f` and returns them together
+    /// with the indices of the tasks referring to any of the others
+    fn partition_waypoints<F: Fn(&Waypoint) -> bool>(
+        &self,
+        f: F,
+    ) -> (Vec<&Waypoint>, HashSet<usize>) {
+        let (waypoints, removed): (Vec<_>, Vec<_>) = self.waypoints.iter().partition(|wp| f(wp));
+
+        let mut removed_names = removed
+            .iter()
+            .map(|wp| wp.name.as_str())
+            .collect::<HashSet<_>>();
+        for waypoint in &waypoints {
+            removed_names.remove(waypoint.name.as_str());
+        }
+
+        let tasks = self.tasks.iter().enumerate();
+        let dropped =
+            tasks.filter(|(_, task)| refers_to(task, |name| removed_names.contains(name)));
+
+        (waypoints, dropped.map(|(idx, _)| idx).collect())
+    }
+
+    /// Returns a file containing only the task at `task_index` and the
+    /// waypoints it refers to, e.g. to share a single task
+    ///
+    /// Inline `Point=` waypoints are kept as part of the task. Returns an
+    /// [`Error::Reference`] for an unknown `task_index`, or listing all task
+    /// points and [multiple starts](Task::multiple_starts) that can't be
+    /// resolved in the waypoint section.
+    pub fn extract_task(&self, task_index: usize) -> Result<CupFile, Error> {
+        let task = self
+            .tasks
+            .get(task_index)
+            .ok_or_else(|| Error::Reference(format!("Unknown task index: {task_index}")))?;
+
+        let names = task.waypoint_names.iter().enumerate();
+        let names = names.filter(|(idx, _)| !task.points.iter().any(|(i, _)| *i as usize == *idx));
+        let names = names.map(|(_, name)| name).chain(&task.multiple_starts);
+
+        let mut referenced = HashSet::new();
+        let mut missing = Vec::new();
+        for name in names {
+            if referenced.insert(name.as_str()) && !self.waypoints.iter().any(|wp| wp.name == *name)
+            {
+                missing.push(format!("'{name}'"));
+            }
+        }
+
+        if !missing.is_empty() {
+            let message = format!("Unresolved waypoints: {}", missing.join(", "));
+            return Err(Error::Reference(message));
+        }
+
+        // Like `resolve_task()`, only the first waypoint of a name is used
+        let waypoints = self.waypoints.iter();
+        let waypoints = waypoints.filter(|wp| referenced.remove(wp.name.as_str()));
+
+        Ok(CupFile {
+            waypoints: waypoints.cloned().collect(),
+            tasks: vec![task.clone()],
+        })
+    }
+
+    /// Splits the file into one file per `country` of the waypoints, e.g. to
+    /// distribute per-country turnpoint files
+    ///
+    /// Waypoints without country are grouped under the `""` key. Each task
+    /// is copied into every file still containing all the waypoints it refers
+    /// to, and dropped from the others, like [`CupFile::filter_waypoints()`]
+    /// does with [`TaskRetention::DropReferencing`]. Tasks referring to
+    /// waypoints of several countries are therefore not part of any of the
+    /// files, see [`CupFile::cross_country_tasks()`].
+    pub fn split_by_country(&self) -> HashMap<String, CupFile> {
+        let countries = self.waypoints.iter().map(|wp| wp.country.as_str());
+        let countries = countries.collect::<HashSet<_>>();
+
+        let files = countries.into_iter().map(|country| {
+            let retention = TaskRetention::DropReferencing;
+            let file = self.filter_waypoints(|wp| wp.country == country, retention);
+            (country.to_string(), file)
+        });
+
+        files.collect()
+    }
+
+    /// Returns the indices of the tasks that [`CupFile::split_by_country()`]
+    /// drops from all files, in ascending order, e.g. to report them
+    pub fn cross_country_tasks(&self) -> Vec<usize> {
+        let countries = self.waypoints.iter().map(|wp| wp.country.as_str());
+        let countries = countries.collect::<HashSet<_>>();
+
+        let mut included = vec![false; self.tasks.len()];
+        for country in countries {
+            let (_, dropped) = self.partition_waypoints(|wp| wp.country == country);
+            for (idx, included) in included.iter_mut().enumerate() {
+                *included |= !dropped.contains(&idx);
+            }
+        }
+
+        let tasks = included.iter().enumerate();
+        let tasks = tasks.filter(|(_, included)| !**included);
+        tasks.map(|(idx, _)| idx).collect()
+    }
+
+    /// Removes waypoints within `tolerance_meters` of another waypoint and
+    /// returns the indices of the removed waypoints, in ascending order
+    ///
+    /// Of two nearby waypoints, the one with a non-empty `code` is kept,
+    /// then the one with the more specific style, e.g. an airfield rather
+    /// than a plain waypoint, and otherwise the earlier one. Tasks are not
+    /// updated, so task points referring to a removed name may become
+    /// unresolvable.
+    pub fn deduplicate_by_coordinate(&mut self, tolerance_meters: f64) -> Vec<usize> {
+        fn rank(waypoint: &Waypoint) -> (bool, u8) {
+            let specificity = match waypoint.style {
+                WaypointStyle::Unknown | WaypointStyle::Other(_) => 0,
+                WaypointStyle::Waypoint => 1,
+                _ => 2,
+            };
+            (!waypoint.code.is_empty(), specificity)
+        }
+
+        let mut kept: Vec<usize> = Vec::new();
+        let mut removed = Vec::new();
+        for (idx, waypoint) in self.waypoints.iter().enumerate() {
+            let duplicate = kept.iter_mut().find(|kept_idx| {
+                self.waypoints[**kept_idx].distance_to(waypoint) <= tolerance_meters
+            });
+
+            match duplicate {
+                Some(kept_idx) if rank(waypoint) > rank(&self.waypoints[*kept_idx]) => {
+                    removed.push(std::mem::replace(kept_idx, idx));
+                }
+                Some(_) => removed.push(idx),
+                None => kept.push(idx),
+            }
+        }
+
+        removed.sort_unstable();
+        let mut idx = 0;
+        self.waypoints.retain(|_| {
+            idx += 1;
+            removed.binary_search(&(idx - 1)).is_err()
+        });
+
+        removed
+    }
+
+    /// Converts all elevations and runway dimensions, including those of
+    /// inline task points, to the given units
+    ///
+    /// The `NearAlt` task option is converted to the elevation unit and
+    /// `NearDis` to the runway unit.
+    pub fn normalize_units(&mut self, elevation: ElevUnit, runway: RunwayUnit) {
+        let inline_points = self.tasks.iter_mut().flat_map(|task| &mut task.points);
+        let inline_points = inline_points.map(|(_, waypoint)| waypoint);
+        for waypoint in self.waypoints.iter_mut().chain(inline_points) {
+            waypoint.elevation = waypoint.elevation.to_unit(elevation);
+            for dimension in [&mut waypoint.runway_length, &mut waypoint.runway_width] {
+                *dimension = dimension.as_ref().map(|d| d.to_unit(runway));
+            }
+        }
+
+        let task_options = self
+            .tasks
+            .iter_mut()
+            .filter_map(|task| task.options.as_mut());
+        for options in task_options {
+            options.near_alt = options.near_alt.as_ref().map(|a| a.to_unit(elevation));
+            options.near_dis = options.near_dis.as_ref().map(|d| d.to_runway_unit(runway));
+        }
+    }
+
+    /// Returns the names shared by more than one waypoint, together with the
+    /// indices of those waypoints, in order of first appearance.
+    ///
+    /// Duplicate names make task references ambiguous, since tasks refer to
+    /// waypoints by name.
+    pub fn duplicate_names(&self) -> Vec<(&str, Vec<usize>)> {
+        let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+        let mut group_by_name = HashMap::new();
+        for (idx, waypoint) in self.waypoints.iter().enumerate() {
+            let name = waypoint.name.as_str();
+            let group = *group_by_name.entry(name).or_insert_with(|| {
+                groups.push((name, Vec::new()));
+                groups.len() - 1
+            });
+            groups[group].1.push(idx);
+        }
+
+        groups.retain(|(_, indices)| indices.len() > 1);
+        groups
+    }
+
+    /// Maps waypoint names to their index in [`waypoints`](Self::waypoints)
+    ///
+    /// For names shared by multiple waypoints, the last index is kept; use
+    /// [`duplicate_names()`](Self::duplicate_names) to detect them.
+    pub fn build_name_index(&self) -> HashMap<&str, usize> {
+        let names = self.waypoints.iter().map(|waypoint| waypoint.name.as_str());
+        names.enumerate().map(|(idx, name)| (name, idx)).collect()
+    }
+
+    /// Maps waypoint codes to their index in [`waypoints`](Self::waypoints)
+    ///
+    /// Waypoints without code are omitted. For codes shared by multiple
+    /// waypoints, the last index is kept.
+    pub fn build_code_index(&self) -> HashMap<&str, usize> {
+        let codes = self.waypoints.iter().map(|waypoint| waypoint.code.as_str());
+        let codes = codes.enumerate().filter(|(_, code)| !code.is_empty());
+        codes.map(|(idx, code)| (code, idx)).collect()
+    }
+
+    /// Formats the waypoints and tasks as a KML document, e.g. for review
+    /// in Google Earth.
+    ///
+    /// Waypoints become point placemarks grouped into one folder per
+    /// [`WaypointStyle`], and tasks become line strings through their points.
+    #[cfg(feature = "kml")]
+    pub fn to_kml(&self) -> Result<String, Error> {
+        export::kml::to_kml(self)
+    }
+
+    pub fn to_bytes_with_encoding(&self, encoding: Encoding) -> Result<Vec<u8>, Error> {
         let mut buf = Vec::new();
-        self.to_writer(&mut buf)?;
+        self.to_writer_with_encoding(&mut buf, encoding)?;
+        Ok(buf)
+    }
+
+    /// Serializes the file as UTF-8
+    ///
+    /// This is equivalent to the infallible [`Display`] implementation, e.g.
+    /// `format!("{cup_file}")`, but takes precedence when calling
+    /// `cup_file.to_string()`.
+    pub fn to_string(&self) -> Result<String, Error> {
+        let buf = self.to_bytes_with_encoding(Encoding::Utf8)?;
         String::from_utf8(buf).map_err(|e| Error::Encoding(e.to_string()))
     }
 }
+
+/// Writes the file in its serialized form, as returned by [`CupFile::to_string()`]
+impl Display for CupFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Formatting into memory without encoding to a legacy charset can't fail
+        let content =
+            writer::format_cup_file(self, &WriteOptions::default()).map_err(|_| std::fmt::Error)?;
+        f.write_str(&content)
+    }
+}
+
+/// Iterates over the waypoints of the waypoint section
+///
+/// Inline waypoints of the tasks are not included, see
+/// [`CupFile::all_waypoints()`] for those.
+impl<'a> IntoIterator for &'a CupFile {
+    type Item = &'a Waypoint;
+    type IntoIter = std::slice::Iter<'a, Waypoint>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.waypoints.iter()
+    }
+}
+
+/// Returns whether any task point not covered by an inline waypoint, or any
+/// of the multiple starts of `task`, has a name matching `f`
+fn refers_to<F: Fn(&str) -> bool>(task: &Task, f: F) -> bool {
+    let mut names = task.waypoint_names.iter().enumerate();
+    let is_task_point = names.any(|(idx, name)| {
+        let is_inline = task.points.iter().any(|(i, _)| *i as usize == idx);
+        !is_inline && f(name)
+    });
+
+    is_task_point || task.multiple_starts.iter().any(|start| f(start))
+}
@@ -0,0 +1,164 @@
+use crate::Encoding;
+
+/// Options controlling how a CUP file is parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// Turn the first issue that would otherwise be reported as a [`Warning`](crate::Warning)
+    /// into an error
+    pub strict: bool,
+    /// Character encoding of the input, or `None` to detect it automatically
+    pub encoding: Option<Encoding>,
+    /// Skip malformed waypoints, including inline `Point=` waypoints of tasks,
+    /// with a warning (`true`) or fail with an error (`false`)
+    pub skip_invalid_waypoints: bool,
+    /// Report recoverable errors, like malformed task lines, as warnings and
+    /// continue parsing instead of failing
+    pub collect_errors: bool,
+    /// Accept `,` as decimal separator in elevation, runway dimension, and
+    /// distance fields, e.g. `"504,5m"`
+    ///
+    /// The specification only allows `.`, but some European exports use `,`.
+    pub accept_comma_decimal: bool,
+    /// Number of lines to skip before the header row, e.g. to ignore a
+    /// [`WriteOptions::header_comment`]
+    pub skip_leading_lines: usize,
+    /// Interpret runway directions from 1 to 36 as runway designators in tens
+    /// of degrees, e.g. `9` as 90° and `36` as 0°
+    ///
+    /// Directions of 1° to 36° can't be expressed when this is enabled.
+    pub runway_direction_in_tens: bool,
+    /// Move `freq` values that don't look like a radio frequency, e.g.
+    /// `123.500`, into an empty `desc` field
+    ///
+    /// Older files without `desc` column placed the description in the
+    /// `freq` column instead.
+    pub legacy_freq_desc: bool,
+    /// Field delimiter of the input, e.g. `b'\t'` for tab-separated files
+    ///
+    /// The specification only allows `,`. If left at `,`, a header row
+    /// without `,` but with `;`, as written by some spreadsheet exports,
+    /// switches to `;` and also enables [`accept_comma_decimal`](Self::accept_comma_decimal).
+    ///
+    /// Since the `pics` field separates its filenames with `;` as well, such
+    /// files need to quote the field to list more than one picture, e.g.
+    /// `"a.jpg;b.jpg"`. Unquoted filenames spill into the following columns
+    /// and are reported as ignored values.
+    pub delimiter: u8,
+    /// Accept coordinates with `:` or a space between degrees and minutes,
+    /// e.g. `51:47.809N` or `014 10.467E`, and minutes with fewer than three
+    /// decimals, e.g. `5147.8N`
+    ///
+    /// The specification only allows the fixed-width form, e.g. `5147.809N`.
+    pub flexible_coordinates: bool,
+    /// Maximum length of a single field in bytes
+    ///
+    /// Records containing a longer field, e.g. in corrupted files, are
+    /// skipped with a warning instead of being processed any further.
+    pub max_field_length: usize,
+    /// Trim leading and trailing whitespace from the `name`, `code`,
+    /// `country`, `freq`, `desc`, and `userdata` fields
+    ///
+    /// By default, whitespace within quoted fields is preserved.
+    pub trim_fields: bool,
+    /// How byte sequences that are invalid in the [`encoding`](Self::encoding)
+    /// are handled
+    ///
+    /// Only UTF-8 has invalid byte sequences. With automatic detection, input
+    /// that isn't valid UTF-8 is decoded with a legacy encoding instead of
+    /// replacing them, and [`DecodeErrorPolicy::Warn`] reports the offset of
+    /// the first one together with the legacy encoding used.
+    pub on_decode_error: DecodeErrorPolicy,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            encoding: None,
+            skip_invalid_waypoints: true,
+            collect_errors: false,
+            accept_comma_decimal: false,
+            skip_leading_lines: 0,
+            runway_direction_in_tens: false,
+            legacy_freq_desc: false,
+            delimiter: b',',
+            flexible_coordinates: false,
+            max_field_length: 1024 * 1024,
+            trim_fields: false,
+            on_decode_error: DecodeErrorPolicy::Replace,
+        }
+    }
+}
+
+/// Handling of invalid byte sequences, see [`ParseOptions::on_decode_error`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecodeErrorPolicy {
+    /// Replace them with `U+FFFD REPLACEMENT CHARACTER`
+    #[default]
+    Replace,
+    /// Replace them and report the offset of the first one as [`Warning`](crate::Warning)
+    Warn,
+    /// Fail with an [`Error::Encoding`](crate::Error::Encoding)
+    Fail,
+}
+
+/// Options controlling how a CUP file is written
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteOptions {
+    /// Character encoding of the output
+    pub encoding: Encoding,
+    /// Whether the output ends with a single newline (`true`) or none at all (`false`)
+    pub trailing_newline: bool,
+    /// Number of decimal places of the coordinate minutes (at most 9)
+    ///
+    /// The specification uses three decimals, which corresponds to a
+    /// resolution of roughly 1.8m.
+    pub coordinate_decimals: u8,
+    /// Line terminator used for all records
+    pub line_ending: LineEnding,
+    /// Line written before the header row, e.g. to name the generator
+    ///
+    /// The CUP format has no comment syntax, so the comment is written as a
+    /// single field. Other readers may reject such files, and this crate only
+    /// reads them back with [`ParseOptions::skip_leading_lines`] set.
+    pub header_comment: Option<String>,
+    /// Whether runway directions are zero-padded to three digits, e.g. `090`,
+    /// like SeeYou does
+    pub pad_runway_direction: bool,
+    /// Whether the `name`, `code`, `desc`, and `pics` fields of waypoints are
+    /// always quoted, like SeeYou does, instead of only when necessary
+    pub always_quote_text_fields: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            encoding: Encoding::Utf8,
+            trailing_newline: true,
+            coordinate_decimals: 3,
+            line_ending: LineEnding::Lf,
+            header_comment: None,
+            pad_runway_direction: true,
+            always_quote_text_fields: false,
+        }
+    }
+}
+
+/// Line terminator for written files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix style `\n`
+    #[default]
+    Lf,
+    /// Windows style `\r\n`
+    Crlf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
@@ -1,3 +1,87 @@
+use crate::ParseOptions;
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// Parses an elevation, runway dimension, or distance field, optionally
+/// accepting `,` as decimal separator
+pub fn parse_dimension<T>(s: &str, options: &ParseOptions) -> Result<T, String>
+where
+    T: FromStr<Err = String>,
+{
+    if options.accept_comma_decimal {
+        s.replace(',', ".").parse()
+    } else {
+        s.parse()
+    }
+}
+
+/// Rewrites a coordinate to the fixed-width form, e.g. `5147.809N`, if
+/// [`ParseOptions::flexible_coordinates`] is enabled
+///
+/// This accepts `:` or a space between degrees and minutes, e.g.
+/// `51:47.809N`, and minutes with fewer than three decimals, e.g.
+/// `5147.8N`. Coordinates that don't match these forms are returned
+/// unchanged.
+pub fn normalize_coordinate<'a>(
+    s: &'a str,
+    degree_digits: usize,
+    options: &ParseOptions,
+) -> Cow<'a, str> {
+    if !options.flexible_coordinates {
+        return Cow::Borrowed(s);
+    }
+
+    let s = match join_degrees_and_minutes(s, degree_digits) {
+        Some(joined) => Cow::Owned(joined),
+        None => Cow::Borrowed(s),
+    };
+
+    match pad_minute_decimals(&s, degree_digits) {
+        Some(padded) => Cow::Owned(padded),
+        None => s,
+    }
+}
+
+/// Joins degrees and minutes separated by `:` or a space, e.g. `51:47.809N`
+fn join_degrees_and_minutes(s: &str, degree_digits: usize) -> Option<String> {
+    let (degrees, minutes) = s.trim().split_once([':', ' '])?;
+
+    let minutes = minutes.trim_start();
+    let minute_digits = minutes.bytes().take_while(u8::is_ascii_digit).count();
+    if degrees.is_empty()
+        || degrees.len() > degree_digits
+        || !degrees.bytes().all(|b| b.is_ascii_digit())
+        || !(1..=2).contains(&minute_digits)
+    {
+        return None;
+    }
+
+    let (whole_minutes, rest) = minutes.split_at(minute_digits);
+    Some(format!(
+        "{degrees:0>degree_digits$}{whole_minutes:0>2}{rest}"
+    ))
+}
+
+/// Pads minutes with fewer than three decimals with zeros, e.g. `5147.8N`
+fn pad_minute_decimals(s: &str, degree_digits: usize) -> Option<String> {
+    let hemisphere = s.bytes().last().filter(u8::is_ascii_alphabetic)?;
+    let value = &s[..s.len() - 1];
+
+    let (whole, decimals) = value.split_once('.').unwrap_or((value, ""));
+    if whole.len() != degree_digits + 2
+        || decimals.len() >= 3
+        || !whole
+            .bytes()
+            .chain(decimals.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let hemisphere = hemisphere as char;
+    Some(format!("{whole}.{decimals:0<3}{hemisphere}"))
+}
+
 pub fn parse_latitude(s: &str) -> Result<f64, String> {
     let bytes = s.as_bytes();
     let bytes_len = bytes.len();
@@ -96,6 +180,43 @@ mod tests {
     use claims::assert_err;
     use proptest::proptest;
 
+    #[test]
+    fn test_normalize_coordinate() {
+        let options = ParseOptions {
+            flexible_coordinates: true,
+            ..Default::default()
+        };
+
+        let cases = [
+            ("51:47.809N", 2, "5147.809N"),
+            ("51 47.809N", 2, "5147.809N"),
+            ("1:7.809S", 2, "0107.809S"),
+            ("014:10.467E", 3, "01410.467E"),
+            ("14: 10.467W", 3, "01410.467W"),
+            ("5147.809N", 2, "5147.809N"),
+            ("0140:10.467E", 3, "0140:10.467E"),
+            (":10.467E", 3, ":10.467E"),
+            ("14:.467E", 3, "14:.467E"),
+            ("5147.8N", 2, "5147.800N"),
+            ("5147.80N", 2, "5147.800N"),
+            ("5147N", 2, "5147.000N"),
+            ("5147.N", 2, "5147.000N"),
+            ("5147.80901N", 2, "5147.80901N"),
+            ("01410.4E", 3, "01410.400E"),
+            ("51:47.8N", 2, "5147.800N"),
+            ("547.8N", 2, "547.8N"),
+            ("5147.8", 2, "5147.8"),
+        ];
+
+        for (input, degree_digits, expected) in cases {
+            let normalized = normalize_coordinate(input, degree_digits, &options);
+            assert_eq!(normalized, expected, "{input}");
+        }
+
+        let normalized = normalize_coordinate("51:47.809N", 2, &ParseOptions::default());
+        assert_eq!(normalized, "51:47.809N");
+    }
+
     #[test]
     fn test_latitude() {
         let cases = [
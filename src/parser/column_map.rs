@@ -1,5 +1,10 @@
+use crate::spec::COLUMNS;
 use csv::StringRecord;
 
+/// Number of leading [`COLUMNS`] that every header row must contain, from
+/// `name` to `style`
+const REQUIRED_COLUMNS: usize = 7;
+
 pub struct ColumnMap {
     pub name: usize,
     pub code: usize,
@@ -15,55 +20,84 @@ pub struct ColumnMap {
     pub desc: Option<usize>,
     pub userdata: Option<usize>,
     pub pics: Option<usize>,
+    pub extra: Vec<(usize, String)>,
+    /// Number of columns of the header row
+    pub len: usize,
+}
+
+impl Default for ColumnMap {
+    /// Column mapping of the standard header row, for files without one
+    fn default() -> Self {
+        Self {
+            name: 0,
+            code: 1,
+            country: 2,
+            lat: 3,
+            lon: 4,
+            elev: 5,
+            style: 6,
+            rwdir: Some(7),
+            rwlen: Some(8),
+            rwwidth: Some(9),
+            freq: Some(10),
+            desc: Some(11),
+            userdata: Some(12),
+            pics: Some(13),
+            extra: Vec::new(),
+            len: COLUMNS.len(),
+        }
+    }
 }
 
 impl TryFrom<&StringRecord> for ColumnMap {
     type Error = String;
 
     fn try_from(record: &StringRecord) -> Result<Self, Self::Error> {
-        let mut name = None;
-        let mut code = None;
-        let mut country = None;
-        let mut lat = None;
-        let mut lon = None;
-        let mut elev = None;
-        let mut style = None;
-        let mut rwdir = None;
-        let mut rwlen = None;
-        let mut rwwidth = None;
-        let mut freq = None;
-        let mut desc = None;
-        let mut userdata = None;
-        let mut pics = None;
+        let mut columns = [None; COLUMNS.len()];
+        let mut extra = Vec::new();
 
         for (idx, header) in record.iter().enumerate() {
-            match header.to_lowercase().as_str() {
-                "name" => name = Some(idx),
-                "code" => code = Some(idx),
-                "country" => country = Some(idx),
-                "lat" => lat = Some(idx),
-                "lon" => lon = Some(idx),
-                "elev" => elev = Some(idx),
-                "style" => style = Some(idx),
-                "rwdir" => rwdir = Some(idx),
-                "rwlen" => rwlen = Some(idx),
-                "rwwidth" => rwwidth = Some(idx),
-                "freq" => freq = Some(idx),
-                "desc" => desc = Some(idx),
-                "userdata" => userdata = Some(idx),
-                "pics" => pics = Some(idx),
-                _ => {}
+            let column = COLUMNS.iter().position(|c| header.eq_ignore_ascii_case(c));
+            match column {
+                Some(column) => columns[column] = Some(idx),
+                None => extra.push((idx, header.to_string())),
             }
         }
 
+        let missing = columns[..REQUIRED_COLUMNS].iter().position(Option::is_none);
+        if let Some(missing) = missing {
+            return Err(format!("Missing required column: {}", COLUMNS[missing]));
+        }
+
+        // Destructured in the order of `COLUMNS`
+        let [
+            Some(name),
+            Some(code),
+            Some(country),
+            Some(lat),
+            Some(lon),
+            Some(elev),
+            Some(style),
+            rwdir,
+            rwlen,
+            rwwidth,
+            freq,
+            desc,
+            userdata,
+            pics,
+        ] = columns
+        else {
+            unreachable!("required columns are checked above");
+        };
+
         Ok(Self {
-            name: name.ok_or("Missing required column: name")?,
-            code: code.ok_or("Missing required column: code")?,
-            country: country.ok_or("Missing required column: country")?,
-            lat: lat.ok_or("Missing required column: lat")?,
-            lon: lon.ok_or("Missing required column: lon")?,
-            elev: elev.ok_or("Missing required column: elev")?,
-            style: style.ok_or("Missing required column: style")?,
+            name,
+            code,
+            country,
+            lat,
+            lon,
+            elev,
+            style,
             rwdir,
             rwlen,
             rwwidth,
@@ -71,6 +105,8 @@ impl TryFrom<&StringRecord> for ColumnMap {
             desc,
             userdata,
             pics,
+            extra,
+            len: record.len(),
         })
     }
 }
@@ -1,76 +1,345 @@
-mod basics;
+pub(crate) mod basics;
 mod column_map;
+mod stream;
 mod task;
 mod waypoint;
 
-use crate::CupFile;
-use crate::Encoding;
-use crate::error::{Error, ParseIssue, Warning};
+use crate::error::{Error, ParseIssue, Severity, Warning};
 use crate::parser::column_map::ColumnMap;
+pub use crate::parser::stream::WaypointsIter;
 use crate::parser::task::parse_tasks;
 use crate::parser::waypoint::parse_waypoints;
-use encoding_rs::{Encoding as EncodingImpl, UTF_8, WINDOWS_1252};
+use crate::spec::TASK_SEPARATOR;
+use crate::{CupFile, DecodeErrorPolicy, Encoding, ParseOptions};
+use csv::StringRecord;
+use encoding_rs::{
+    DecoderResult, Encoding as EncodingImpl, ISO_8859_2, UTF_8, WINDOWS_1250, WINDOWS_1252,
+};
 use std::borrow::Cow;
 use std::io::Read;
 
-pub const TASK_SEPARATOR: &str = "-----Related Tasks-----";
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
 
 pub fn parse<R: Read>(
     mut reader: R,
-    encoding: Option<Encoding>,
+    options: &ParseOptions,
 ) -> Result<(CupFile, Vec<Warning>), Error> {
     let mut bytes = Vec::new();
     reader.read_to_end(&mut bytes)?;
 
-    let content = match encoding {
-        Some(enc) => decode_with_encoding(&bytes, enc)?,
-        None => decode_auto(&bytes)?,
+    parse_bytes(&bytes, options)
+}
+
+pub fn parse_bytes(bytes: &[u8], options: &ParseOptions) -> Result<(CupFile, Vec<Warning>), Error> {
+    let mut warnings = Vec::new();
+    let content = match options.encoding {
+        Some(enc) => decode_with_encoding(bytes, enc, options, &mut warnings)?,
+        None => decode_auto(bytes, options, &mut warnings)?,
     };
+    check_strict(&warnings, options)?;
 
-    parse_content(&content)
+    parse_content(&content, options, warnings)
 }
 
-fn decode_with_encoding(bytes: &[u8], encoding: Encoding) -> Result<Cow<'_, str>, Error> {
+fn decode_with_encoding<'a>(
+    bytes: &'a [u8],
+    encoding: Encoding,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<Cow<'a, str>, Error> {
+    let input = bytes;
+    let bytes = strip_bom(bytes)?;
+
     let encoding_impl: &'static EncodingImpl = match encoding {
         Encoding::Utf8 => UTF_8,
         Encoding::Windows1252 => WINDOWS_1252,
+        Encoding::Latin2 => ISO_8859_2,
+        Encoding::Windows1250 => WINDOWS_1250,
     };
 
-    let (content, _, _had_errors) = encoding_impl.decode(bytes);
+    let (content, had_errors) = encoding_impl.decode_without_bom_handling(bytes);
+    if !had_errors || options.on_decode_error == DecodeErrorPolicy::Replace {
+        return Ok(content);
+    }
+
+    // Reports offsets and lines of the input, including any BOM
+    let offset = first_malformed_offset(bytes, encoding_impl).unwrap_or_default();
+    let offset = offset + input.len() - bytes.len();
+    let message = format!("Invalid {encoding} byte sequence at byte offset {offset}");
+    report_decode_error(
+        input,
+        offset,
+        message,
+        "replaced with U+FFFD",
+        options,
+        warnings,
+    )?;
+
     Ok(content)
 }
 
-fn decode_auto(bytes: &[u8]) -> Result<Cow<'_, str>, Error> {
+/// Fails with `message` or reports it as warning, depending on
+/// [`ParseOptions::on_decode_error`], with `fallback` describing how the
+/// invalid byte sequence at `offset` of the `input` was decoded instead
+fn report_decode_error(
+    input: &[u8],
+    offset: usize,
+    message: String,
+    fallback: &str,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<(), Error> {
+    if options.on_decode_error == DecodeErrorPolicy::Fail {
+        return Err(Error::Encoding(message));
+    }
+
+    let line = input[..offset].iter().filter(|b| **b == b'\n').count() + 1;
+    let message = format!("{message}, {fallback}");
+    let issue = ParseIssue::new(message).with_line(Some(line as u64));
+    warnings.push(issue.with_severity(Severity::Warning).into());
+
+    Ok(())
+}
+
+/// Returns the byte offset of the first malformed byte sequence, if any
+fn first_malformed_offset(bytes: &[u8], encoding: &'static EncodingImpl) -> Option<usize> {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut buffer = [0; 4096];
+    let mut offset = 0;
+    loop {
+        let (result, read, _) =
+            decoder.decode_to_utf8_without_replacement(&bytes[offset..], &mut buffer, true);
+        offset += read;
+
+        match result {
+            DecoderResult::InputEmpty => return None,
+            DecoderResult::OutputFull => continue,
+            DecoderResult::Malformed(len, consumed) => {
+                return Some(offset - len as usize - consumed as usize);
+            }
+        }
+    }
+}
+
+fn decode_auto<'a>(
+    bytes: &'a [u8],
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<Cow<'a, str>, Error> {
+    let input = bytes;
+    let bytes = strip_bom(bytes)?;
+
     // Try UTF-8 first (strict)
     match std::str::from_utf8(bytes) {
         Ok(s) => Ok(s.into()),
-        Err(_) => {
-            // Fall back to Windows-1252 (never fails, maps all bytes)
-            let (content, _, _) = WINDOWS_1252.decode(bytes);
+        Err(error) => {
+            // Fall back to a legacy encoding (never fails, maps all bytes)
+            let legacy = guess_legacy_encoding(bytes);
+            if options.on_decode_error != DecodeErrorPolicy::Replace {
+                let offset = error.valid_up_to() + input.len() - bytes.len();
+                let message = format!("Invalid UTF-8 byte sequence at byte offset {offset}");
+                let fallback = format!("decoded as {} instead", legacy.name());
+                report_decode_error(input, offset, message, &fallback, options, warnings)?;
+            }
+
+            let (content, _) = legacy.decode_without_bom_handling(bytes);
             Ok(content)
         }
     }
 }
 
-fn parse_content(content: &str) -> Result<(CupFile, Vec<Warning>), Error> {
+/// Guesses the most likely legacy encoding from the content
+#[cfg(feature = "charset-detect")]
+fn guess_legacy_encoding(bytes: &[u8]) -> &'static EncodingImpl {
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, false)
+}
+
+/// Assumes Windows-1252, which is what most legacy files use
+#[cfg(not(feature = "charset-detect"))]
+fn guess_legacy_encoding(_bytes: &[u8]) -> &'static EncodingImpl {
+    WINDOWS_1252
+}
+
+#[cfg(feature = "charset-detect")]
+pub fn detect_encoding(bytes: &[u8]) -> Option<Encoding> {
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    if std::str::from_utf8(bytes).is_ok() {
+        return Some(Encoding::Utf8);
+    }
+
+    match guess_legacy_encoding(bytes) {
+        encoding if encoding == WINDOWS_1252 => Some(Encoding::Windows1252),
+        encoding if encoding == ISO_8859_2 => Some(Encoding::Latin2),
+        encoding if encoding == WINDOWS_1250 => Some(Encoding::Windows1250),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the line separates the waypoint and task sections
+///
+/// Besides the exact [`TASK_SEPARATOR`], this accepts any number (at least
+/// three) of dashes, surrounding whitespace, and any casing, e.g.
+/// `---- related tasks ----`.
+fn is_task_separator(line: &str) -> bool {
+    fn strip_dashes(s: &str, strip: impl Fn(&str) -> &str) -> Option<&str> {
+        let stripped = strip(s);
+        (s.len() - stripped.len() >= 3).then_some(stripped)
+    }
+
+    if line == TASK_SEPARATOR {
+        return true;
+    }
+
+    let line = line.trim();
+    let Some(line) = strip_dashes(line, |s| s.trim_start_matches('-')) else {
+        return false;
+    };
+    let Some(line) = strip_dashes(line, |s| s.trim_end_matches('-')) else {
+        return false;
+    };
+
+    line.trim().eq_ignore_ascii_case("Related Tasks")
+}
+
+/// Strips a leading UTF-8 byte order mark and rejects UTF-16 encoded input
+fn strip_bom(bytes: &[u8]) -> Result<&[u8], Error> {
+    if bytes.starts_with(UTF16_LE_BOM) || bytes.starts_with(UTF16_BE_BOM) {
+        let message = "UTF-16 encoded files are not supported, please convert to UTF-8";
+        return Err(Error::Encoding(message.into()));
+    }
+
+    Ok(bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes))
+}
+
+fn parse_content(
+    content: &str,
+    options: &ParseOptions,
+    mut warnings: Vec<Warning>,
+) -> Result<(CupFile, Vec<Warning>), Error> {
     let content = content.trim();
     if content.is_empty() {
         return Err(ParseIssue::new("Empty file").into());
     }
 
-    let mut warnings = Vec::new();
+    let semicolon_options;
+    let options = if is_semicolon_delimited(content, options) {
+        semicolon_options = ParseOptions {
+            delimiter: b';',
+            accept_comma_decimal: true,
+            ..options.clone()
+        };
+        &semicolon_options
+    } else {
+        options
+    };
 
     let mut csv_reader = csv::ReaderBuilder::new()
         .flexible(true)
+        .has_headers(false)
+        .delimiter(options.delimiter)
         .from_reader(content.as_bytes());
 
-    let headers = csv_reader.headers()?;
-    let column_map = ColumnMap::try_from(headers)
-        .map_err(|error| ParseIssue::new(error).with_record(headers))?;
-
     let mut csv_iter = csv_reader.records();
-    let waypoints = parse_waypoints(&mut csv_iter, &column_map, &mut warnings)?;
-    let tasks = parse_tasks(&mut csv_iter, &column_map, &mut warnings)?;
 
+    // Skipped lines may not be valid CSV, so errors are ignored as well
+    for _ in 0..options.skip_leading_lines {
+        csv_iter.next();
+    }
+
+    let headers = csv_iter.next().transpose()?.unwrap_or_default();
+    if is_task_separator(headers.as_slice()) {
+        return parse_tasks_only(&mut csv_iter, options, warnings);
+    }
+
+    let column_map = ColumnMap::try_from(&headers)
+        .map_err(|error| ParseIssue::new(error).with_record(&headers).into());
+    let Some(column_map) = recover(column_map, options, &mut warnings)? else {
+        return Ok((CupFile::default(), warnings));
+    };
+
+    let waypoints = parse_waypoints(&mut csv_iter, &column_map, options, &mut warnings)?;
+    let tasks = parse_tasks(&mut csv_iter, &column_map, options, &mut warnings)?;
+
+    Ok((CupFile { waypoints, tasks }, warnings))
+}
+
+/// Returns `true` if the delimiter is left at `,` and the header row uses
+/// `;` instead, e.g. `name;code;country;lat;lon;elev;style`
+fn is_semicolon_delimited(content: &str, options: &ParseOptions) -> bool {
+    let header = content.lines().nth(options.skip_leading_lines);
+    let header = header.unwrap_or_default();
+    options.delimiter == b',' && !header.contains(',') && header.contains(';')
+}
+
+/// Parses a file without waypoint section, which starts with the task
+/// separator and only contains inline `Point=` waypoints
+///
+/// Inline waypoints use the standard column order, and task points that
+/// aren't defined inline are reported as warnings.
+fn parse_tasks_only(
+    csv_iter: &mut csv::StringRecordsIter<&[u8]>,
+    options: &ParseOptions,
+    mut warnings: Vec<Warning>,
+) -> Result<(CupFile, Vec<Warning>), Error> {
+    let column_map = ColumnMap::default();
+    let tasks = parse_tasks(csv_iter, &column_map, options, &mut warnings)?;
+
+    for task in &tasks {
+        for (idx, name) in task.waypoint_names.iter().enumerate() {
+            if !task.points.iter().any(|(i, _)| *i as usize == idx) {
+                let message = format!("Unresolved task point: '{name}' (no waypoint section)");
+                warnings.push(ParseIssue::new(message).into());
+            }
+        }
+    }
+    check_strict(&warnings, options)?;
+
+    let waypoints = Vec::new();
     Ok((CupFile { waypoints, tasks }, warnings))
 }
+
+/// Reports a recoverable error as a warning when collecting errors, or
+/// returns it otherwise
+fn recover<T>(
+    result: Result<T, Error>,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<Option<T>, Error> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::Parse(issue)) if options.collect_errors => {
+            warnings.push(issue.with_severity(Severity::Error).into());
+            Ok(None)
+        }
+        Err(Error::Csv(error)) if options.collect_errors => {
+            let line = error.position().map(|p| p.line());
+            let issue = ParseIssue::new(error.to_string()).with_line(line);
+            warnings.push(issue.with_severity(Severity::Error).into());
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns an issue for records containing a field longer than
+/// [`ParseOptions::max_field_length`], which are skipped
+fn check_field_length(record: &StringRecord, options: &ParseOptions) -> Option<ParseIssue> {
+    let max = options.max_field_length;
+    let len = record.iter().map(str::len).find(|len| *len > max)?;
+
+    let message = format!("Skipped record: Field too long: {len} bytes (must be at most {max})");
+    let issue = ParseIssue::new(message).with_record(record);
+    Some(issue.with_severity(Severity::Error))
+}
+
+/// Turns the first collected warning into an error when parsing in strict mode
+fn check_strict(warnings: &[Warning], options: &ParseOptions) -> Result<(), Error> {
+    match warnings.first() {
+        Some(warning) if options.strict => Err(warning.0.clone().into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,203 @@
+use crate::error::ParseIssue;
+use crate::parser::column_map::ColumnMap;
+use crate::parser::waypoint::parse_waypoint;
+use crate::parser::{is_task_separator, strip_bom};
+use crate::{Error, ParseOptions, Waypoint};
+use csv::StringRecord;
+use encoding_rs::{Decoder, UTF_8, WINDOWS_1252};
+use std::io::{self, BufRead, Read};
+
+/// Iterator over the waypoints of a CUP file, parsing one record at a time
+pub struct WaypointsIter<R> {
+    reader: Option<csv::Reader<DecodingReader<R>>>,
+    column_map: Option<ColumnMap>,
+    error: Option<Error>,
+    record: StringRecord,
+}
+
+impl<R: BufRead> WaypointsIter<R> {
+    pub fn new(mut reader: R) -> Self {
+        let (reader, error) = match skip_bom(&mut reader) {
+            Ok(()) => {
+                let reader = csv::ReaderBuilder::new()
+                    .flexible(true)
+                    .from_reader(DecodingReader::new(reader));
+                (Some(reader), None)
+            }
+            Err(error) => (None, Some(error)),
+        };
+
+        let column_map = None;
+        let record = StringRecord::new();
+        Self {
+            reader,
+            column_map,
+            error,
+            record,
+        }
+    }
+
+    fn read_column_map(reader: &mut csv::Reader<DecodingReader<R>>) -> Result<ColumnMap, Error> {
+        let headers = reader.headers()?;
+        ColumnMap::try_from(headers)
+            .map_err(|error| ParseIssue::new(error).with_record(headers).into())
+    }
+}
+
+impl<R: BufRead> Iterator for WaypointsIter<R> {
+    type Item = Result<Waypoint, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+
+        let reader = self.reader.as_mut()?;
+
+        let column_map = match &self.column_map {
+            Some(column_map) => column_map,
+            None => match Self::read_column_map(reader) {
+                Ok(column_map) => self.column_map.insert(column_map),
+                Err(error) => {
+                    self.reader = None;
+                    return Some(Err(error));
+                }
+            },
+        };
+
+        match reader.read_record(&mut self.record) {
+            Ok(true) => {}
+            Ok(false) => {
+                self.reader = None;
+                return None;
+            }
+            Err(error) => return Some(Err(error.into())),
+        }
+
+        let record = &self.record;
+        if is_task_separator(record.as_slice()) {
+            self.reader = None;
+            return None;
+        }
+
+        // Field-level warnings can't be reported through the iterator
+        let mut warnings = Vec::new();
+        let options = ParseOptions::default();
+        let result = parse_waypoint(column_map, record, &options, &mut warnings)
+            .map_err(|error| ParseIssue::new(error).with_record(record).into());
+
+        Some(result)
+    }
+}
+
+/// Skips a leading UTF-8 byte order mark and rejects UTF-16 encoded input
+fn skip_bom<R: BufRead>(reader: &mut R) -> Result<(), Error> {
+    let chunk = reader.fill_buf()?;
+    let bom_len = chunk.len() - strip_bom(chunk)?.len();
+    reader.consume(bom_len);
+    Ok(())
+}
+
+/// Decodes the input to UTF-8 on the fly
+///
+/// Like the buffered parser, this prefers UTF-8 and falls back to
+/// Windows-1252. The decision is made on the first non-ASCII character, so
+/// files that are mostly valid UTF-8 decode with replacement characters
+/// instead.
+struct DecodingReader<R> {
+    inner: R,
+    decoder: Option<Decoder>,
+    /// Undecoded input while the encoding is still undecided
+    pending: Vec<u8>,
+    output: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: BufRead> DecodingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            decoder: None,
+            pending: Vec::new(),
+            output: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Refills the output buffer, leaving it empty only at the end of the input
+    fn fill_output(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.pos = 0;
+
+        while self.output.is_empty() && !self.eof {
+            let chunk = self.inner.fill_buf()?;
+            let len = chunk.len();
+            self.eof = len == 0;
+
+            match &mut self.decoder {
+                Some(decoder) => {
+                    decode(decoder, chunk, self.eof, &mut self.output);
+                    self.inner.consume(len);
+                }
+                None => {
+                    self.pending.extend_from_slice(chunk);
+                    self.inner.consume(len);
+                    self.detect_encoding();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Passes ASCII input through until the first non-ASCII character
+    /// determines the encoding of the remaining input
+    fn detect_encoding(&mut self) {
+        let Some(idx) = self.pending.iter().position(|b| !b.is_ascii()) else {
+            self.output.append(&mut self.pending);
+            return;
+        };
+
+        let is_utf8 = match std::str::from_utf8(&self.pending[idx..]) {
+            Ok(_) => true,
+            Err(error) if error.valid_up_to() > 0 => true,
+            // The character continues in the next chunk
+            Err(error) if error.error_len().is_none() && !self.eof => return,
+            Err(_) => false,
+        };
+
+        let encoding = if is_utf8 { UTF_8 } else { WINDOWS_1252 };
+        let decoder = self
+            .decoder
+            .insert(encoding.new_decoder_without_bom_handling());
+        decode(decoder, &self.pending, self.eof, &mut self.output);
+        self.pending.clear();
+    }
+}
+
+fn decode(decoder: &mut Decoder, input: &[u8], last: bool, output: &mut Vec<u8>) {
+    let max_len = decoder
+        .max_utf8_buffer_length(input.len())
+        .expect("input chunk too large");
+
+    let start = output.len();
+    output.resize(start + max_len, 0);
+    let (_, _, written, _) = decoder.decode_to_utf8(input, &mut output[start..], last);
+    output.truncate(start + written);
+}
+
+impl<R: BufRead> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.output.len() {
+            self.fill_output()?;
+        }
+
+        let available = &self.output[self.pos..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
@@ -1,30 +1,47 @@
-use crate::error::ParseIssue;
+use crate::error::{ParseIssue, Severity};
+use crate::parser::basics::parse_dimension;
 use crate::parser::column_map::ColumnMap;
-use crate::parser::waypoint;
-use crate::{Error, ObsZoneStyle, ObservationZone, Task, TaskOptions, Warning, Waypoint};
+use crate::parser::{check_field_length, check_strict, recover, waypoint};
+use crate::spec::{OBS_ZONE, OPTIONS, POINT, STARTS};
+use crate::{
+    Degrees, Error, HalfAngle, ObsZoneStyle, ObservationZone, ParseOptions, Task, TaskOptions,
+    Warning, Waypoint,
+};
 use csv::StringRecord;
 
 pub fn parse_tasks(
     csv_iter: &mut csv::StringRecordsIter<&[u8]>,
     column_map: &ColumnMap,
+    options: &ParseOptions,
     warnings: &mut Vec<Warning>,
 ) -> Result<Vec<Task>, Error> {
     let mut tasks = Vec::new();
 
     let mut csv_iter = csv_iter.peekable();
     'outer: while let Some(result) = csv_iter.next() {
-        let record = result?;
+        let Some(record) = recover(result.map_err(Error::from), options, warnings)? else {
+            continue;
+        };
+
+        if let Some(issue) = check_field_length(&record, options) {
+            warnings.push(issue.into());
+            check_strict(warnings, options)?;
+            continue;
+        }
 
         let line = record.as_byte_record().as_slice();
-        if line.starts_with(b"Options")
-            || line.starts_with(b"ObsZone=")
-            || line.starts_with(b"Point=")
-            || line.starts_with(b"STARTS=")
+        if is_blank_record(&record)
+            || line.starts_with(OPTIONS.as_bytes())
+            || starts_with_key(line, OBS_ZONE)
+            || starts_with_key(line, POINT)
+            || starts_with_key(line, STARTS)
         {
             continue;
         }
 
-        let mut task = parse_task_line(&record)?;
+        let Some(mut task) = recover(parse_task_line(&record), options, warnings)? else {
+            continue;
+        };
 
         // Look ahead for Options, ObsZone, Point, and STARTS lines
         while let Some(result) = csv_iter.peek() {
@@ -34,19 +51,49 @@ pub fn parse_tasks(
 
             let next_line = record.as_byte_record().as_slice();
 
-            if next_line.starts_with(b"Options") {
-                task.options = Some(parse_options_line(record)?);
+            if is_blank_record(record) {
+                // Blank lines don't end the attribute lines of a task
                 csv_iter.next();
-            } else if next_line.starts_with(b"ObsZone=") {
-                task.observation_zones.push(parse_obszone_line(record)?);
+            } else if let Some(issue) = check_field_length(record, options) {
+                warnings.push(issue.into());
                 csv_iter.next();
-            } else if next_line.starts_with(b"Point=") {
-                let (point_index, inline_waypoint) =
-                    parse_inline_waypoint_line_with_index(record, column_map, warnings)?;
-                // Add the inline waypoint to the points field
-                task.points.push((point_index as u32, inline_waypoint));
+            } else if next_line.starts_with(OPTIONS.as_bytes()) {
+                let task_options = parse_options_line(record, options);
+                if let Some(task_options) = recover(task_options, options, warnings)? {
+                    if task.options.is_some() {
+                        let message = "Duplicate Options line, previous options are ignored";
+                        let issue = ParseIssue::new(message.to_string()).with_record(record);
+                        warnings.push(issue.with_severity(Severity::Warning).into());
+                    }
+                    task.options = Some(task_options);
+                }
                 csv_iter.next();
-            } else if next_line.starts_with(b"STARTS=") {
+            } else if starts_with_key(next_line, OBS_ZONE) {
+                let obs_zone = parse_obszone_line(record, options, warnings);
+                if let Some(obs_zone) = recover(obs_zone, options, warnings)? {
+                    task.observation_zones.push(obs_zone);
+                }
+                csv_iter.next();
+            } else if starts_with_key(next_line, POINT) {
+                let point =
+                    parse_inline_waypoint_line_with_index(record, column_map, options, warnings);
+                if let Some(Some((point_index, inline_waypoint))) =
+                    recover(point, options, warnings)?
+                {
+                    let point_index = point_index as u32;
+                    if task.points.iter().any(|(idx, _)| *idx == point_index) {
+                        // The first inline waypoint for an index is kept
+                        let message =
+                            format!("Skipped record: Duplicate point index {point_index}");
+                        let issue = ParseIssue::new(message).with_record(record);
+                        warnings.push(issue.with_severity(Severity::Error).into());
+                    } else {
+                        // Add the inline waypoint to the points field
+                        task.points.push((point_index, inline_waypoint));
+                    }
+                }
+                csv_iter.next();
+            } else if starts_with_key(next_line, STARTS) {
                 task.multiple_starts = parse_starts_line(record)?;
                 csv_iter.next();
             } else {
@@ -54,22 +101,48 @@ pub fn parse_tasks(
             }
         }
 
+        check_strict(warnings, options)?;
+
         tasks.push(task);
     }
 
     Ok(tasks)
 }
 
+/// Returns `true` for records without content, e.g. lines consisting only of
+/// whitespace or commas
+///
+/// A line consisting of `""` is not blank, since it's written for tasks
+/// without description and waypoint names, e.g. tasks using only inline points.
+fn is_blank_record(record: &StringRecord) -> bool {
+    let is_unnamed_task = record.len() == 1 && record[0].is_empty();
+    !is_unnamed_task && record.iter().all(|field| field.trim().is_empty())
+}
+
+/// Returns `true` if the line starts with `key=`, e.g. `ObsZone=`
+fn starts_with_key(line: &[u8], key: &str) -> bool {
+    let rest = line.strip_prefix(key.as_bytes());
+    rest.is_some_and(|rest| rest.starts_with(b"="))
+}
+
+/// Strips a leading `key=` from the field, if present
+fn strip_key<'a>(field: &'a str, key: &str) -> &'a str {
+    let rest = field
+        .strip_prefix(key)
+        .and_then(|rest| rest.strip_prefix('='));
+    rest.unwrap_or(field)
+}
+
 fn parse_task_line(record: &StringRecord) -> Result<Task, Error> {
     if record.is_empty() {
-        return Err(ParseIssue::new("Empty task line").into());
+        return Err(ParseIssue::new("Empty task line")
+            .with_record(record)
+            .into());
     }
 
-    let description = if record.get(0).map(|s| s.is_empty()).unwrap_or(true) {
-        None
-    } else {
-        Some(record.get(0).unwrap().to_string())
-    };
+    // Whitespace-only descriptions are treated like empty ones
+    let description = record.get(0).filter(|s| !s.trim().is_empty());
+    let description = description.map(|s| s.to_string());
 
     let waypoint_names = record
         .iter()
@@ -88,8 +161,13 @@ fn parse_task_line(record: &StringRecord) -> Result<Task, Error> {
     })
 }
 
-fn parse_options_line(record: &StringRecord) -> Result<TaskOptions, Error> {
+fn parse_options_line(
+    record: &StringRecord,
+    parse_options: &ParseOptions,
+) -> Result<TaskOptions, Error> {
     // Options,NoStart=12:34:56,TaskTime=01:45:12,WpDis=False,NearDis=0.7km,NearAlt=300.0m
+    let issue = |message: String| ParseIssue::new(message).with_record(record);
+
     let mut options = TaskOptions {
         no_start: None,
         task_time: None,
@@ -102,23 +180,34 @@ fn parse_options_line(record: &StringRecord) -> Result<TaskOptions, Error> {
         before_pts: None,
         after_pts: None,
         bonus: None,
+        extra_options: Vec::new(),
     };
 
     for part in record.iter().skip(1) {
         if let Some((key, value)) = part.split_once('=') {
+            let (key, value) = (key.trim(), value.trim());
             match key {
                 "NoStart" => options.no_start = Some(value.to_string()),
                 "TaskTime" => options.task_time = Some(value.to_string()),
                 "WpDis" => options.wp_dis = Some(value.eq_ignore_ascii_case("true")),
-                "NearDis" => options.near_dis = Some(value.parse().map_err(ParseIssue::new)?),
-                "NearAlt" => options.near_alt = Some(value.parse().map_err(ParseIssue::new)?),
+                "NearDis" => {
+                    let near_dis = parse_dimension(value, parse_options).map_err(issue)?;
+                    options.near_dis = Some(near_dis);
+                }
+                "NearAlt" => {
+                    let near_alt = parse_dimension(value, parse_options).map_err(issue)?;
+                    options.near_alt = Some(near_alt);
+                }
                 "MinDis" => options.min_dis = Some(value.eq_ignore_ascii_case("true")),
                 "RandomOrder" => options.random_order = Some(value.eq_ignore_ascii_case("true")),
                 "MaxPts" => options.max_pts = value.parse().ok(),
                 "BeforePts" => options.before_pts = value.parse().ok(),
                 "AfterPts" => options.after_pts = value.parse().ok(),
                 "Bonus" => options.bonus = value.parse().ok(),
-                _ => {}
+                _ => {
+                    let option = (key.to_string(), value.to_string());
+                    options.extra_options.push(option);
+                }
             }
         }
     }
@@ -126,8 +215,14 @@ fn parse_options_line(record: &StringRecord) -> Result<TaskOptions, Error> {
     Ok(options)
 }
 
-fn parse_obszone_line(record: &StringRecord) -> Result<ObservationZone, Error> {
+fn parse_obszone_line(
+    record: &StringRecord,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<ObservationZone, Error> {
     // ObsZone=0,Style=2,R1=400m,A1=180,Line=1
+    let issue = |message: String| ParseIssue::new(message).with_record(record);
+
     let mut index = None;
     let mut style = None;
     let mut r1 = None;
@@ -139,26 +234,27 @@ fn parse_obszone_line(record: &StringRecord) -> Result<ObservationZone, Error> {
 
     for part in record.iter() {
         if let Some((key, value)) = part.split_once('=') {
+            let (key, value) = (key.trim(), value.trim());
             match key {
-                "ObsZone" => index = value.parse().ok(),
+                OBS_ZONE => index = value.parse().ok(),
                 "Style" => {
                     if let Ok(val) = value.parse::<u8>() {
                         style = ObsZoneStyle::from_u8(val);
                     }
                 }
-                "R1" => r1 = Some(value.parse().map_err(ParseIssue::new)?),
-                "A1" => a1 = value.parse().ok(),
-                "R2" => r2 = Some(value.parse().map_err(ParseIssue::new)?),
-                "A2" => a2 = value.parse().ok(),
-                "A12" => a12 = value.parse().ok(),
-                "Line" => line_val = Some(value == "1" || value.eq_ignore_ascii_case("true")),
+                "R1" => r1 = Some(parse_dimension(value, options).map_err(issue)?),
+                "A1" => a1 = parse_half_angle(key, value, record, warnings),
+                "R2" => r2 = Some(parse_dimension(value, options).map_err(issue)?),
+                "A2" => a2 = parse_half_angle(key, value, record, warnings),
+                "A12" => a12 = parse_bearing(key, value, record, warnings),
+                "Line" => line_val = parse_line_flag(value),
                 _ => {}
             }
         }
     }
 
-    let index = index.ok_or_else(|| ParseIssue::new("Missing ObsZone index"))?;
-    let style = style.ok_or_else(|| ParseIssue::new("Missing ObsZone style"))?;
+    let index = index.ok_or_else(|| issue("Missing ObsZone index".into()))?;
+    let style = style.ok_or_else(|| issue("Missing ObsZone style".into()))?;
 
     Ok(ObservationZone {
         index,
@@ -172,43 +268,130 @@ fn parse_obszone_line(record: &StringRecord) -> Result<ObservationZone, Error> {
     })
 }
 
+/// Parses a half-angle, reporting values that are clamped to `[0, 180]`
+fn parse_half_angle(
+    key: &str,
+    value: &str,
+    record: &StringRecord,
+    warnings: &mut Vec<Warning>,
+) -> Option<HalfAngle> {
+    let parsed = value.parse::<f64>().ok()?;
+    let half_angle = HalfAngle::new(parsed);
+    if half_angle.value() != parsed {
+        let message = format!("Clamped {key}={value} to {half_angle}");
+        let issue = ParseIssue::new(message).with_record(record);
+        warnings.push(issue.with_severity(Severity::Warning).into());
+    }
+
+    Some(half_angle)
+}
+
+/// Parses a bearing, reporting values that are normalized to `[0, 360)`
+fn parse_bearing(
+    key: &str,
+    value: &str,
+    record: &StringRecord,
+    warnings: &mut Vec<Warning>,
+) -> Option<Degrees> {
+    let parsed = value.parse::<f64>().ok()?;
+    let bearing = Degrees::new(parsed);
+    if bearing.value() != parsed {
+        // The bearing is still the same, only its value has changed
+        let message = format!("Normalized {key}={value} to {bearing}");
+        let issue = ParseIssue::new(message).with_record(record);
+        warnings.push(issue.with_severity(Severity::Info).into());
+    }
+
+    Some(bearing)
+}
+
+/// Parses the `Line` value of an observation zone, which SeeYou writes as
+/// `1`/`0` and this crate as `True`/`False`
+fn parse_line_flag(value: &str) -> Option<bool> {
+    match value {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ if value.eq_ignore_ascii_case("true") => Some(true),
+        _ if value.eq_ignore_ascii_case("false") => Some(false),
+        _ => None,
+    }
+}
+
 fn parse_starts_line(record: &StringRecord) -> Result<Vec<String>, Error> {
-    // STARTS=Celovec,Hodos,Ratitovec,Jamnik
-    Ok(record
-        .iter()
-        .enumerate()
-        .map(|(i, start)| {
-            if i == 0 {
-                start.strip_prefix("STARTS=").unwrap_or(start)
-            } else {
-                start
-            }
-        })
+    // STARTS="Celovec","Hodos","Ratitovec","Jamnik"
+    //
+    // The quotes of the first name directly follow the `STARTS=` key, so the
+    // CSV reader doesn't recognize them. A first name containing commas is
+    // split across multiple fields, which are joined again here, before its
+    // quotes are removed. All other names are regular CSV fields.
+    let mut fields = record.iter();
+    let mut first = strip_key(fields.next().unwrap_or_default(), STARTS).to_string();
+    while is_unterminated_quote(&first) {
+        let Some(field) = fields.next() else {
+            break;
+        };
+        first.push(',');
+        first.push_str(field);
+    }
+
+    let first = unquote(first.trim());
+    let starts = std::iter::once(first.as_str()).chain(fields);
+
+    Ok(starts
         .map(|s| s.trim())
         .filter(|s| !s.is_empty())
         .map(|s| s.to_string())
         .collect())
 }
 
+/// Returns whether `value` starts with a quote that isn't closed yet
+fn is_unterminated_quote(value: &str) -> bool {
+    value.trim_start().starts_with('"') && value.matches('"').count() % 2 == 1
+}
+
+/// Removes the surrounding quotes of a quoted CSV field and unescapes `""`
+fn unquote(value: &str) -> String {
+    let inner = value.strip_prefix('"').and_then(|s| s.strip_suffix('"'));
+    match inner {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => value.to_string(),
+    }
+}
+
+/// Parses a `Point=` line, returning `None` for malformed waypoints that are
+/// skipped with a warning, see [`ParseOptions::skip_invalid_waypoints`]
 fn parse_inline_waypoint_line_with_index(
     record: &StringRecord,
     column_map: &ColumnMap,
+    options: &ParseOptions,
     warnings: &mut Vec<Warning>,
-) -> Result<(usize, Waypoint), Error> {
+) -> Result<Option<(usize, Waypoint)>, Error> {
     // Format: Point=1,"Point_3",PNT_3,,4627.136N,01412.856E,0.0m,1,,,,,,,
 
     // Extract the point index
-    let point_idx_str = record[0].trim_start_matches("Point=");
-    let point_index = point_idx_str
-        .parse::<usize>()
-        .map_err(|_| ParseIssue::new(format!("Invalid point index: '{point_idx_str}'")))?;
+    let point_idx_str = strip_key(&record[0], POINT);
+    let point_index = point_idx_str.parse::<usize>().map_err(|_| {
+        let message = format!("Invalid point index: '{point_idx_str}'");
+        ParseIssue::new(message).with_record(record)
+    })?;
 
     // Skip the Point=N field and create a proper waypoint record
-    let waypoint_record = StringRecord::from(record.iter().skip(1).collect::<Vec<_>>());
+    let mut waypoint_record = StringRecord::from(record.iter().skip(1).collect::<Vec<_>>());
+    waypoint_record.set_position(record.position().cloned());
 
     // Parse as a normal waypoint using the same headers as the waypoint section
-    let waypoint = waypoint::parse_waypoint(column_map, &waypoint_record, warnings)
-        .map_err(|error| ParseIssue::new(error).with_record(&waypoint_record))?;
+    let waypoint = match waypoint::parse_waypoint(column_map, &waypoint_record, options, warnings) {
+        Ok(waypoint) => waypoint,
+        Err(error) if !options.skip_invalid_waypoints => {
+            return Err(ParseIssue::new(error).with_record(&waypoint_record).into());
+        }
+        Err(error) => {
+            let message = format!("Skipped waypoint: {error}");
+            let issue = ParseIssue::new(message).with_record(&waypoint_record);
+            warnings.push(issue.with_severity(Severity::Error).into());
+            return Ok(None);
+        }
+    };
 
-    Ok((point_index, waypoint))
+    Ok(Some((point_index, waypoint)))
 }
@@ -1,31 +1,48 @@
-use crate::error::ParseIssue;
-use crate::parser::TASK_SEPARATOR;
-use crate::parser::basics::{parse_latitude, parse_longitude};
+use crate::error::{ParseIssue, Severity};
+use crate::parser::basics::{
+    normalize_coordinate, parse_dimension, parse_latitude, parse_longitude,
+};
 use crate::parser::column_map::ColumnMap;
-use crate::{Error, Warning, Waypoint, WaypointStyle};
+use crate::parser::{check_field_length, check_strict, is_task_separator, recover};
+use crate::{Error, ParseOptions, Warning, Waypoint, WaypointStyle};
 use csv::StringRecord;
 
 pub fn parse_waypoints(
     csv_iter: &mut csv::StringRecordsIter<&[u8]>,
     column_map: &ColumnMap,
+    options: &ParseOptions,
     warnings: &mut Vec<Warning>,
 ) -> Result<Vec<Waypoint>, Error> {
     let mut waypoints = Vec::new();
     for result in csv_iter {
-        let record = result?;
+        let Some(record) = recover(result.map_err(Error::from), options, warnings)? else {
+            continue;
+        };
 
-        let line = record.as_slice();
-        if line == TASK_SEPARATOR {
+        if is_task_separator(record.as_slice()) {
             break;
         }
 
-        match parse_waypoint(column_map, &record, warnings) {
+        if let Some(issue) = check_field_length(&record, options) {
+            warnings.push(issue.into());
+            check_strict(warnings, options)?;
+            continue;
+        }
+
+        match parse_waypoint(column_map, &record, options, warnings) {
             Ok(waypoint) => waypoints.push(waypoint),
+            Err(error) if !options.skip_invalid_waypoints => {
+                let error = ParseIssue::new(error).with_record(&record).into();
+                recover::<()>(Err(error), options, warnings)?;
+            }
             Err(error) => {
                 let message = format!("Skipped waypoint: {error}");
-                warnings.push(ParseIssue::new(message).with_record(&record).into())
+                let issue = ParseIssue::new(message).with_record(&record);
+                warnings.push(issue.with_severity(Severity::Error).into())
             }
         }
+
+        check_strict(warnings, options)?;
     }
 
     Ok(waypoints)
@@ -34,82 +51,127 @@ pub fn parse_waypoints(
 pub fn parse_waypoint(
     column_map: &ColumnMap,
     record: &StringRecord,
+    options: &ParseOptions,
     warnings: &mut Vec<Warning>,
 ) -> Result<Waypoint, String> {
-    let name = record.get(column_map.name).unwrap_or_default();
+    let text = |idx: usize| {
+        let value = record.get(idx).unwrap_or_default();
+        if options.trim_fields {
+            value.trim()
+        } else {
+            value
+        }
+    };
+
+    let name = text(column_map.name);
     if name.is_empty() {
         return Err("Name field cannot be empty".into());
     }
 
     let name = name.to_string();
 
-    let code = record.get(column_map.code).unwrap_or_default().to_string();
-    let country = record
-        .get(column_map.country)
-        .unwrap_or_default()
-        .to_string();
+    let code = text(column_map.code).to_string();
+    let country = text(column_map.country).to_string();
 
     let lat_str = record.get(column_map.lat).unwrap_or_default();
-    let latitude = parse_latitude(lat_str)?;
+    let latitude = parse_latitude(&normalize_coordinate(lat_str, 2, options))?;
 
     let lon_str = record.get(column_map.lon).unwrap_or_default();
-    let longitude = parse_longitude(lon_str)?;
+    let longitude = parse_longitude(&normalize_coordinate(lon_str, 3, options))?;
 
     let elev_str = record.get(column_map.elev).unwrap_or_default();
-    let elevation = elev_str.parse()?;
+    let elevation = parse_dimension(elev_str, options)?;
 
     let style_str = record.get(column_map.style).unwrap_or_default();
     let style = match parse_waypoint_style(style_str) {
         Some(style) => style,
         None => {
             let message = format!("Ignored field: Unknown waypoint style: '{style_str}'");
-            warnings.push(ParseIssue::new(message).with_record(record).into());
+            let issue = ParseIssue::new(message).with_record(record);
+            warnings.push(issue.with_severity(Severity::Warning).into());
             WaypointStyle::Unknown
         }
     };
 
     let runway_direction = column_map.rwdir.and_then(|idx| record.get(idx));
     let runway_direction = runway_direction.filter(|s| !s.is_empty());
-    let runway_direction = runway_direction.map(parse_runway_direction).transpose();
+    let runway_direction = runway_direction.map(|s| parse_runway_direction(s, options));
+    let runway_direction = runway_direction.transpose();
     let runway_direction = runway_direction
         .inspect_err(|error| {
             let message = format!("Ignored field: {error}");
-            warnings.push(ParseIssue::new(message).with_record(record).into())
+            let issue = ParseIssue::new(message).with_record(record);
+            warnings.push(issue.with_severity(Severity::Warning).into())
         })
         .unwrap_or_default();
 
     let runway_length = column_map.rwlen.and_then(|idx| record.get(idx));
     let runway_length = runway_length.filter(|s| !s.is_empty());
-    let runway_length = runway_length.map(|s| s.parse()).transpose();
+    let runway_length = runway_length
+        .map(|s| parse_dimension(s, options))
+        .transpose();
     let runway_length = runway_length
         .inspect_err(|error| {
             let message = format!("Ignored field: {error}");
-            warnings.push(ParseIssue::new(message).with_record(record).into())
+            let issue = ParseIssue::new(message).with_record(record);
+            warnings.push(issue.with_severity(Severity::Warning).into())
         })
         .unwrap_or_default();
 
     let runway_width = column_map.rwwidth.and_then(|idx| record.get(idx));
     let runway_width = runway_width.filter(|s| !s.is_empty());
-    let runway_width = runway_width.map(|s| s.parse()).transpose();
+    let runway_width = runway_width
+        .map(|s| parse_dimension(s, options))
+        .transpose();
     let runway_width = runway_width
         .inspect_err(|error| {
             let message = format!("Ignored field: {error}");
-            warnings.push(ParseIssue::new(message).with_record(record).into())
+            let issue = ParseIssue::new(message).with_record(record);
+            warnings.push(issue.with_severity(Severity::Warning).into())
         })
         .unwrap_or_default();
 
-    let frequency = column_map.freq.and_then(|idx| record.get(idx));
+    let frequency = column_map.freq.map(text);
     let frequency = frequency.unwrap_or_default().to_string();
 
-    let description = column_map.desc.and_then(|idx| record.get(idx));
+    let description = column_map.desc.map(text);
     let description = description.unwrap_or_default().to_string();
 
-    let userdata = column_map.userdata.and_then(|idx| record.get(idx));
+    let (frequency, description) = if options.legacy_freq_desc
+        && description.is_empty()
+        && !frequency.is_empty()
+        && !is_radio_frequency(&frequency)
+    {
+        (String::new(), frequency)
+    } else {
+        (frequency, description)
+    };
+
+    let userdata = column_map.userdata.map(text);
     let userdata = userdata.unwrap_or_default().to_string();
 
     let pictures = column_map.pics.and_then(|idx| record.get(idx));
     let pictures = pictures.map(parse_pictures).unwrap_or_default();
 
+    let extra = column_map.extra.iter();
+    let extra = extra.map(|(idx, header)| {
+        let value = record.get(*idx).unwrap_or_default();
+        (header.clone(), value.to_string())
+    });
+    let extra = extra.collect();
+
+    let stray_values = record.iter().skip(column_map.len);
+    let stray_values = stray_values.filter(|value| !value.trim().is_empty());
+    let stray_values = stray_values
+        .map(|value| format!("'{value}'"))
+        .collect::<Vec<_>>();
+    if !stray_values.is_empty() {
+        let stray_values = stray_values.join(", ");
+        let message = format!("Ignored field: Values beyond the header columns: {stray_values}");
+        let issue = ParseIssue::new(message).with_record(record);
+        warnings.push(issue.with_severity(Severity::Warning).into());
+    }
+
     Ok(Waypoint {
         name,
         code,
@@ -125,44 +187,62 @@ pub fn parse_waypoint(
         description,
         userdata,
         pictures,
+        extra,
     })
 }
 
+/// Parses a numeric style code, ignoring surrounding whitespace and retaining
+/// codes unknown to this library as [`WaypointStyle::Other`]
 fn parse_waypoint_style(s: &str) -> Option<WaypointStyle> {
-    Some(match s {
-        "0" => WaypointStyle::Unknown,
-        "1" => WaypointStyle::Waypoint,
-        "2" => WaypointStyle::GrassAirfield,
-        "3" => WaypointStyle::Outlanding,
-        "4" => WaypointStyle::GlidingAirfield,
-        "5" => WaypointStyle::SolidAirfield,
-        "6" => WaypointStyle::MountainPass,
-        "7" => WaypointStyle::MountainTop,
-        "8" => WaypointStyle::TransmitterMast,
-        "9" => WaypointStyle::Vor,
-        "10" => WaypointStyle::Ndb,
-        "11" => WaypointStyle::CoolingTower,
-        "12" => WaypointStyle::Dam,
-        "13" => WaypointStyle::Tunnel,
-        "14" => WaypointStyle::Bridge,
-        "15" => WaypointStyle::PowerPlant,
-        "16" => WaypointStyle::Castle,
-        "17" => WaypointStyle::Intersection,
-        "18" => WaypointStyle::Marker,
-        "19" => WaypointStyle::ControlPoint,
-        "20" => WaypointStyle::PgTakeOff,
-        "21" => WaypointStyle::PgLandingZone,
-        _ => return None,
-    })
+    let value = s.trim().parse::<u8>().ok()?;
+    Some(WaypointStyle::from_u8(value).unwrap_or(WaypointStyle::Other(value)))
 }
 
-fn parse_runway_direction(s: &str) -> Result<u16, String> {
-    s.parse()
-        .map_err(|_| format!("Invalid runway direction: '{s}'"))
+/// Parses a runway direction in degrees, normalizing `360` to `0`
+fn parse_runway_direction(s: &str, options: &ParseOptions) -> Result<u16, String> {
+    let value: u16 = s
+        .parse()
+        .map_err(|_| format!("Invalid runway direction: '{s}'"))?;
+
+    match value {
+        1..=36 if options.runway_direction_in_tens => Ok(value * 10 % 360),
+        360 => Ok(0),
+        0..360 => Ok(value),
+        _ => Err(format!(
+            "Runway direction out of range: '{s}' (must be between 0 and 360)"
+        )),
+    }
 }
 
+/// Returns `true` for values in `123.45` or `123.456` notation
+fn is_radio_frequency(s: &str) -> bool {
+    let Some((mhz, khz)) = s.trim().split_once('.') else {
+        return false;
+    };
+
+    mhz.len() == 3
+        && (2..=3).contains(&khz.len())
+        && mhz.bytes().chain(khz.bytes()).all(|b| b.is_ascii_digit())
+}
+
+/// Splits the `pics` field on `;`, where `\;` and `\\` are escaped
+/// characters within a filename
 fn parse_pictures(s: &str) -> Vec<String> {
-    s.split(';')
+    let mut pictures = Vec::new();
+    let mut picture = String::new();
+
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some(';' | '\\')) => picture.extend(chars.next()),
+            ';' => pictures.push(std::mem::take(&mut picture)),
+            c => picture.push(c),
+        }
+    }
+    pictures.push(picture);
+
+    pictures
+        .into_iter()
         .map(|p| p.trim().to_string())
         .filter(|p| !p.is_empty())
         .collect()
@@ -345,3 +345,24 @@
 //! ```
 //!
 //! Waypoints must be defined, observation zones are the same for all possible starts.
+
+/// Waypoint columns in the canonical order of the header row
+pub const COLUMNS: [&str; 14] = [
+    "name", "code", "country", "lat", "lon", "elev", "style", "rwdir", "rwlen", "rwwidth", "freq",
+    "desc", "userdata", "pics",
+];
+
+/// Line separating the waypoints from the tasks
+pub const TASK_SEPARATOR: &str = "-----Related Tasks-----";
+
+/// Keyword of the task options line, e.g. `Options,NoStart=12:34:56`
+pub const OPTIONS: &str = "Options";
+
+/// Keyword of observation zone lines, e.g. `ObsZone=0,Style=2,R1=400m`
+pub const OBS_ZONE: &str = "ObsZone";
+
+/// Keyword of inline waypoint lines, e.g. `Point=1,"Point_3",PNT_3,...`
+pub const POINT: &str = "Point";
+
+/// Keyword of the multiple starts line, e.g. `STARTS=Celovec,Hodos`
+pub const STARTS: &str = "STARTS";
@@ -0,0 +1,108 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Angle in degrees, normalized to `[0, 360)`, e.g. a bearing
+///
+/// Formatted without trailing `.0` for whole degrees, e.g. `45` or `22.5`,
+/// like SeeYou does.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "f64", into = "f64")
+)]
+pub struct Degrees(f64);
+
+impl Degrees {
+    pub fn new(value: f64) -> Self {
+        // `rem_euclid()` may round up to 360 for tiny negative values, and
+        // adding zero turns -0 into 0
+        let value = value.rem_euclid(360.0) + 0.0;
+        Self(if value >= 360.0 { 0.0 } else { value })
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl From<f64> for Degrees {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Degrees> for f64 {
+    fn from(degrees: Degrees) -> Self {
+        degrees.0
+    }
+}
+
+impl Display for Degrees {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Degrees {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(parse_angle(s)?))
+    }
+}
+
+/// Half-angle of an observation zone sector in degrees, clamped to `[0, 180]`
+///
+/// Unlike [`Degrees`], half-angles don't wrap around, e.g. `360` is a full
+/// circle rather than an empty sector. Formatted like [`Degrees`].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "f64", into = "f64")
+)]
+pub struct HalfAngle(f64);
+
+impl HalfAngle {
+    pub fn new(value: f64) -> Self {
+        // Adding zero turns -0 into 0
+        Self(value.clamp(0.0, 180.0) + 0.0)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl From<f64> for HalfAngle {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<HalfAngle> for f64 {
+    fn from(half_angle: HalfAngle) -> Self {
+        half_angle.0
+    }
+}
+
+impl Display for HalfAngle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for HalfAngle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(parse_angle(s)?))
+    }
+}
+
+fn parse_angle(s: &str) -> Result<f64, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| format!("Invalid angle: '{s}'"))
+}
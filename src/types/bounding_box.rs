@@ -0,0 +1,59 @@
+use crate::Waypoint;
+
+/// Geographic extent of a set of waypoints in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Southern edge
+    pub min_lat: f64,
+    /// Western edge
+    pub min_lon: f64,
+    /// Northern edge
+    pub max_lat: f64,
+    /// Eastern edge
+    pub max_lon: f64,
+    /// Whether the box extends eastwards from `min_lon` across ±180° to
+    /// `max_lon`, in which case `min_lon` is greater than `max_lon`
+    pub crosses_antimeridian: bool,
+}
+
+impl BoundingBox {
+    /// Returns the smallest bounding box containing all `waypoints`, or `None`
+    /// if there are no waypoints.
+    pub fn from_waypoints<'a>(waypoints: impl IntoIterator<Item = &'a Waypoint>) -> Option<Self> {
+        let mut min_lat = f64::INFINITY;
+        let mut max_lat = f64::NEG_INFINITY;
+        let mut longitudes = Vec::new();
+
+        for waypoint in waypoints {
+            min_lat = min_lat.min(waypoint.latitude);
+            max_lat = max_lat.max(waypoint.latitude);
+            longitudes.push(waypoint.longitude);
+        }
+
+        longitudes.sort_by(f64::total_cmp);
+        let (&first, &last) = (longitudes.first()?, longitudes.last()?);
+
+        // The box leaves out the largest gap between neighboring longitudes.
+        // If that gap is not the one wrapping around ±180° the box has to
+        // cross the antimeridian instead.
+        let wrap_gap = 360.0 - (last - first);
+        let largest_gap = longitudes
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0], pair[0], pair[1]))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .filter(|(gap, _, _)| *gap > wrap_gap);
+
+        let (min_lon, max_lon, crosses_antimeridian) = match largest_gap {
+            Some((_, west_of_gap, east_of_gap)) => (east_of_gap, west_of_gap, true),
+            None => (first, last, false),
+        };
+
+        Some(Self {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+            crosses_antimeridian,
+        })
+    }
+}
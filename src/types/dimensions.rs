@@ -1,25 +1,57 @@
 use crate::FromStr;
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Sub};
 
 macro_rules! dimension_enum {
     (
         $(#[$meta:meta])*
         $name:ident,
         $display_name:literal,
+        $format:literal,
         [
             $( $variant:ident = $suffix:literal ),* $(,)?
         ]
     ) => {
         $(#[$meta])*
-        #[derive(Debug, Clone, PartialEq, PartialOrd)]
+        ///
+        /// Values are ordered by their length in meters, regardless of their
+        /// unit. Since equality compares unit and value, equally long values
+        /// in different units are unordered; use `cmp_meters()` to treat them
+        /// as equal instead.
+        #[derive(Debug, Clone, PartialEq)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde::Serialize, serde::Deserialize),
+            serde(tag = "unit", content = "value")
+        )]
         pub enum $name {
-            $( $variant(f64) ),*
+            $(
+                #[cfg_attr(feature = "serde", serde(rename = $suffix))]
+                $variant(f64)
+            ),*
+        }
+
+        impl $name {
+            /// Compares two values by their length in meters, regardless of their unit
+            pub fn cmp_meters(&self, other: &Self) -> Option<Ordering> {
+                self.to_meters().partial_cmp(&other.to_meters())
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                match self.cmp_meters(other)? {
+                    Ordering::Equal if self != other => None,
+                    ordering => Some(ordering),
+                }
+            }
         }
 
         impl Display for $name {
             fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
                 match self {
-                    $( $name::$variant(value) => write!(f, "{value}{}", $suffix) ),*
+                    $( $name::$variant(value) => write!(f, $format, value, $suffix) ),*
                 }
             }
         }
@@ -31,17 +63,22 @@ macro_rules! dimension_enum {
                 let s = s.trim();
 
                 $(
-                    if let Some(value_str) = s.strip_suffix($suffix) {
-                        let value: f64 = value_str
-                            .parse()
-                            .map_err(|_| format!("Invalid {}: '{s}'", $display_name))?;
+                    let value = s.strip_suffix($suffix).and_then(|v| v.parse().ok());
+                    if let Some(value) = value {
                         return Ok($name::$variant(value));
                     }
                 )*
 
-                if let Some(unit_start) = s.chars().position(|c| c.is_alphabetic()) {
-                    let unit = &s[unit_start..];
-                    return Err(format!("Invalid {} unit: '{unit}'", $display_name));
+                // Distinguish unknown units like `500km` from malformed numbers
+                let (value_str, unit) = s.split_at(s.trim_end_matches(char::is_alphabetic).len());
+                let units = [$($suffix),*];
+                if !unit.is_empty() && !units.contains(&unit) && value_str.parse::<f64>().is_ok() {
+                    let (last, others) = units.split_last().unwrap();
+                    return Err(format!(
+                        "Unknown {} unit: '{unit}' (expected {} or {last})",
+                        $display_name,
+                        others.join(", "),
+                    ));
                 }
 
                 let value: f64 = s
@@ -55,12 +92,23 @@ macro_rules! dimension_enum {
 
 dimension_enum!(
     /// Elevation measurement with unit
+    ///
+    /// Formatted with exactly one decimal place, e.g. `504.0m`, like SeeYou does.
     Elevation,
     "elevation",
+    "{:.1}{}",
     [Feet = "ft", Meters = "m"]
 );
 
 impl Elevation {
+    pub const fn from_meters(meters: f64) -> Self {
+        Elevation::Meters(meters)
+    }
+
+    pub const fn from_feet(feet: f64) -> Self {
+        Elevation::Feet(feet)
+    }
+
     pub fn to_meters(&self) -> f64 {
         match self {
             Elevation::Meters(m) => *m,
@@ -74,16 +122,75 @@ impl Elevation {
             Elevation::Feet(ft) => *ft,
         }
     }
+
+    /// Converts the elevation to the given unit
+    pub fn to_unit(&self, unit: ElevUnit) -> Elevation {
+        match unit {
+            ElevUnit::Feet => Elevation::Feet(self.to_feet()),
+            ElevUnit::Meters => Elevation::Meters(self.to_meters()),
+        }
+    }
+}
+
+/// Unit of an [`Elevation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ElevUnit {
+    Feet,
+    Meters,
+}
+
+/// Adds two elevations, keeping the unit if both share it and returning
+/// [`Elevation::Meters`] otherwise
+impl Add for Elevation {
+    type Output = Elevation;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Elevation::Meters(a), Elevation::Meters(b)) => Elevation::Meters(a + b),
+            (Elevation::Feet(a), Elevation::Feet(b)) => Elevation::Feet(a + b),
+            (a, b) => Elevation::Meters(a.to_meters() + b.to_meters()),
+        }
+    }
+}
+
+/// Subtracts two elevations, keeping the unit if both share it and returning
+/// [`Elevation::Meters`] otherwise
+impl Sub for Elevation {
+    type Output = Elevation;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Elevation::Meters(a), Elevation::Meters(b)) => Elevation::Meters(a - b),
+            (Elevation::Feet(a), Elevation::Feet(b)) => Elevation::Feet(a - b),
+            (a, b) => Elevation::Meters(a.to_meters() - b.to_meters()),
+        }
+    }
 }
 
 dimension_enum!(
     /// Runway dimension measurement with unit
+    ///
+    /// Formatted with exactly one decimal place, e.g. `1130.0m`, like SeeYou does.
     RunwayDimension,
     "runway dimension",
+    "{:.1}{}",
     [NauticalMiles = "nm", StatuteMiles = "ml", Meters = "m"]
 );
 
 impl RunwayDimension {
+    pub const fn from_meters(meters: f64) -> Self {
+        RunwayDimension::Meters(meters)
+    }
+
+    pub const fn from_nautical_miles(nautical_miles: f64) -> Self {
+        RunwayDimension::NauticalMiles(nautical_miles)
+    }
+
+    pub const fn from_statute_miles(statute_miles: f64) -> Self {
+        RunwayDimension::StatuteMiles(statute_miles)
+    }
+
     pub fn to_meters(&self) -> f64 {
         match self {
             RunwayDimension::Meters(m) => *m,
@@ -91,12 +198,41 @@ impl RunwayDimension {
             RunwayDimension::StatuteMiles(mi) => mi * 1609.344,
         }
     }
+
+    /// Converts the runway dimension to the given unit
+    pub fn to_unit(&self, unit: RunwayUnit) -> RunwayDimension {
+        match (unit, self) {
+            (RunwayUnit::Meters, _) => RunwayDimension::Meters(self.to_meters()),
+            (RunwayUnit::NauticalMiles, RunwayDimension::NauticalMiles(nm)) => {
+                RunwayDimension::NauticalMiles(*nm)
+            }
+            (RunwayUnit::NauticalMiles, _) => {
+                RunwayDimension::NauticalMiles(self.to_meters() / 1852.0)
+            }
+            (RunwayUnit::StatuteMiles, RunwayDimension::StatuteMiles(mi)) => {
+                RunwayDimension::StatuteMiles(*mi)
+            }
+            (RunwayUnit::StatuteMiles, _) => {
+                RunwayDimension::StatuteMiles(self.to_meters() / 1609.344)
+            }
+        }
+    }
+}
+
+/// Unit of a [`RunwayDimension`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RunwayUnit {
+    NauticalMiles,
+    StatuteMiles,
+    Meters,
 }
 
 dimension_enum!(
     /// Distance measurement with unit
     Distance,
     "distance",
+    "{}{}",
     [
         Kilometers = "km",
         NauticalMiles = "nm",
@@ -106,6 +242,22 @@ dimension_enum!(
 );
 
 impl Distance {
+    pub const fn from_meters(meters: f64) -> Self {
+        Distance::Meters(meters)
+    }
+
+    pub const fn from_kilometers(kilometers: f64) -> Self {
+        Distance::Kilometers(kilometers)
+    }
+
+    pub const fn from_nautical_miles(nautical_miles: f64) -> Self {
+        Distance::NauticalMiles(nautical_miles)
+    }
+
+    pub const fn from_statute_miles(statute_miles: f64) -> Self {
+        Distance::StatuteMiles(statute_miles)
+    }
+
     pub fn to_meters(&self) -> f64 {
         match self {
             Distance::Meters(m) => *m,
@@ -114,4 +266,46 @@ impl Distance {
             Distance::StatuteMiles(mi) => mi * 1609.344,
         }
     }
+
+    /// Converts the distance to the given runway unit, which are a subset of
+    /// the distance units
+    pub(crate) fn to_runway_unit(&self, unit: RunwayUnit) -> Distance {
+        match (unit, self) {
+            (RunwayUnit::Meters, _) => Distance::Meters(self.to_meters()),
+            (RunwayUnit::NauticalMiles, Distance::NauticalMiles(nm)) => {
+                Distance::NauticalMiles(*nm)
+            }
+            (RunwayUnit::NauticalMiles, _) => Distance::NauticalMiles(self.to_meters() / 1852.0),
+            (RunwayUnit::StatuteMiles, Distance::StatuteMiles(mi)) => Distance::StatuteMiles(*mi),
+            (RunwayUnit::StatuteMiles, _) => Distance::StatuteMiles(self.to_meters() / 1609.344),
+        }
+    }
+}
+
+/// Scales the distance, keeping its unit
+impl Mul<f64> for Distance {
+    type Output = Distance;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        match self {
+            Distance::Meters(m) => Distance::Meters(m * rhs),
+            Distance::Kilometers(km) => Distance::Kilometers(km * rhs),
+            Distance::NauticalMiles(nm) => Distance::NauticalMiles(nm * rhs),
+            Distance::StatuteMiles(mi) => Distance::StatuteMiles(mi * rhs),
+        }
+    }
+}
+
+/// Divides the distance, keeping its unit
+impl Div<f64> for Distance {
+    type Output = Distance;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        match self {
+            Distance::Meters(m) => Distance::Meters(m / rhs),
+            Distance::Kilometers(km) => Distance::Kilometers(km / rhs),
+            Distance::NauticalMiles(nm) => Distance::NauticalMiles(nm / rhs),
+            Distance::StatuteMiles(mi) => Distance::StatuteMiles(mi / rhs),
+        }
+    }
 }
@@ -1,7 +1,15 @@
+mod angle;
+mod bounding_box;
 mod dimensions;
+mod statistics;
 mod task;
+mod time;
 mod waypoint;
 
+pub use angle::*;
+pub use bounding_box::*;
 pub use dimensions::*;
+pub use statistics::*;
 pub use task::*;
+pub use time::*;
 pub use waypoint::*;
@@ -0,0 +1,48 @@
+use crate::{CupFile, WaypointStyle};
+use std::collections::{BTreeSet, HashMap};
+
+/// Summary of the contents of a [`CupFile`], see [`CupFile::statistics()`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    /// Number of waypoints in the waypoint section
+    pub waypoint_count: usize,
+    /// Number of tasks
+    pub task_count: usize,
+    /// Number of waypoints per style
+    pub style_counts: HashMap<WaypointStyle, usize>,
+    /// Distinct non-empty `country` values of the waypoints
+    pub countries: BTreeSet<String>,
+    /// Lowest and highest waypoint elevation in meters, or `None` if there
+    /// are no waypoints
+    pub elevation_range: Option<(f64, f64)>,
+}
+
+impl Statistics {
+    pub(crate) fn from_cup_file(cup_file: &CupFile) -> Self {
+        let mut style_counts = HashMap::new();
+        let mut countries = BTreeSet::new();
+        let mut elevation_range: Option<(f64, f64)> = None;
+
+        for waypoint in &cup_file.waypoints {
+            *style_counts.entry(waypoint.style).or_default() += 1;
+
+            if !waypoint.country.is_empty() {
+                countries.insert(waypoint.country.clone());
+            }
+
+            let elevation = waypoint.elevation.to_meters();
+            elevation_range = Some(match elevation_range {
+                Some((min, max)) => (min.min(elevation), max.max(elevation)),
+                None => (elevation, elevation),
+            });
+        }
+
+        Self {
+            waypoint_count: cup_file.waypoints.len(),
+            task_count: cup_file.tasks.len(),
+            style_counts,
+            countries,
+            elevation_range,
+        }
+    }
+}
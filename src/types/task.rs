@@ -1,8 +1,10 @@
+use crate::error::ParseIssue;
 use crate::types::waypoint::Waypoint;
-use crate::{Distance, Elevation};
+use crate::{CupFile, CupTime, Degrees, Distance, Elevation, Error, HalfAngle};
 
 /// Task definition from a CUP file
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Task {
     /// Task description
     pub description: Option<String>,
@@ -18,8 +20,61 @@ pub struct Task {
     pub multiple_starts: Vec<String>,
 }
 
+impl Task {
+    /// Returns the description, or `""` if the task has none
+    pub fn description_or_default(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+
+    /// Appends a task point referring to a waypoint of the waypoint section
+    /// by name
+    pub fn push_waypoint(&mut self, name: impl Into<String>) {
+        self.waypoint_names.push(name.into());
+    }
+
+    /// Appends a task point defined inline by a `Point=` line, using the
+    /// next task point index
+    pub fn push_inline_point(&mut self, waypoint: Waypoint) {
+        let index = self.waypoint_names.len() as u32;
+        self.waypoint_names.push(waypoint.name.clone());
+        self.points.push((index, waypoint));
+    }
+
+    /// Returns the observation zone of the task point at `index`, if any
+    pub fn observation_zone_for(&self, index: u32) -> Option<&ObservationZone> {
+        self.observation_zones
+            .iter()
+            .find(|zone| zone.index == index)
+    }
+
+    /// Returns the initial true bearing in degrees `[0, 360)` of each leg of
+    /// the task, or `None` if any task point can't be resolved in `file`.
+    pub fn leg_bearings(&self, file: &CupFile) -> Option<Vec<f64>> {
+        let waypoints = file.resolve_task(self).ok()?;
+        let bearings = waypoints
+            .windows(2)
+            .map(|leg| leg[0].bearing_to(leg[1]))
+            .collect();
+
+        Some(bearings)
+    }
+
+    /// Formats the observation zones of the task as OpenAir airspaces, e.g.
+    /// for display alongside airspace data.
+    ///
+    /// Cylinders become `DC` circles and sectors become `DB` arcs around the
+    /// resolved task point. The zone orientation follows its
+    /// [`ObsZoneStyle`]. Returns an error if any task point can't be
+    /// resolved in `file`.
+    #[cfg(feature = "openair")]
+    pub fn export_observation_zones_openair(&self, file: &CupFile) -> Result<String, Error> {
+        crate::export::openair::to_openair(self, file)
+    }
+}
+
 /// Task options and constraints
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaskOptions {
     /// Opening of start line
     pub no_start: Option<String>,
@@ -43,10 +98,150 @@ pub struct TaskOptions {
     pub after_pts: Option<u32>,
     /// Bonus for crossing the finish line
     pub bonus: Option<f64>,
+    /// Unrecognized options as `(key, value)` pairs, in file order
+    pub extra_options: Vec<(String, String)>,
+}
+
+impl TaskOptions {
+    /// Returns a builder for task options with only some values set
+    ///
+    /// ```
+    /// # use seeyou_cup::{Distance, TaskOptions};
+    /// let options = TaskOptions::builder()
+    ///     .task_time("05:00:00")
+    ///     .wp_dis(true)
+    ///     .near_dis(Distance::Kilometers(1.5))
+    ///     .build();
+    ///
+    /// assert_eq!(options.task_time.as_deref(), Some("05:00:00"));
+    /// assert_eq!(options.no_start, None);
+    /// ```
+    pub fn builder() -> TaskOptionsBuilder {
+        TaskOptionsBuilder::default()
+    }
+
+    /// Returns the parsed opening time of the start line, if present
+    ///
+    /// The raw [`no_start`](Self::no_start) value is kept as-is, so this
+    /// returns an error for values not in `HH:MM:SS` notation.
+    pub fn no_start_time(&self) -> Option<Result<CupTime, Error>> {
+        self.no_start.as_deref().map(parse_time)
+    }
+
+    /// Returns the parsed designated task time, if present
+    ///
+    /// The raw [`task_time`](Self::task_time) value is kept as-is, so this
+    /// returns an error for values not in `HH:MM:SS` notation.
+    pub fn task_duration(&self) -> Option<Result<CupTime, Error>> {
+        self.task_time.as_deref().map(parse_time)
+    }
+
+    /// Checks that the point options are consistent, e.g. before exporting
+    /// a task from an editor
+    ///
+    /// The mandatory points at the beginning and end (`BeforePts` and
+    /// `AfterPts`) must not exceed `MaxPts`, and only apply to tasks with
+    /// random order of waypoints, so they can't be combined with an explicit
+    /// `RandomOrder=False`.
+    pub fn validate(&self) -> Result<(), String> {
+        let before_pts = self.before_pts.unwrap_or_default();
+        let after_pts = self.after_pts.unwrap_or_default();
+
+        if let (Some(before), Some(after), Some(max)) =
+            (self.before_pts, self.after_pts, self.max_pts)
+            && before as u64 + after as u64 > max as u64
+        {
+            return Err(format!(
+                "BeforePts ({before}) and AfterPts ({after}) exceed MaxPts ({max})"
+            ));
+        }
+
+        if self.random_order == Some(false) && (before_pts > 0 || after_pts > 0) {
+            return Err(format!(
+                "BeforePts ({before_pts}) and AfterPts ({after_pts}) require RandomOrder"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Builder for [`TaskOptions`], created by [`TaskOptions::builder()`]
+#[derive(Debug, Clone, Default)]
+pub struct TaskOptionsBuilder {
+    options: TaskOptions,
+}
+
+impl TaskOptionsBuilder {
+    /// Sets the opening of the start line, e.g. `"12:34:56"`
+    pub fn no_start(mut self, no_start: impl Into<String>) -> Self {
+        self.options.no_start = Some(no_start.into());
+        self
+    }
+
+    /// Sets the designated time for the task, e.g. `"05:00:00"`
+    pub fn task_time(mut self, task_time: impl Into<String>) -> Self {
+        self.options.task_time = Some(task_time.into());
+        self
+    }
+
+    pub fn wp_dis(mut self, wp_dis: bool) -> Self {
+        self.options.wp_dis = Some(wp_dis);
+        self
+    }
+
+    pub fn near_dis(mut self, near_dis: Distance) -> Self {
+        self.options.near_dis = Some(near_dis);
+        self
+    }
+
+    pub fn near_alt(mut self, near_alt: Elevation) -> Self {
+        self.options.near_alt = Some(near_alt);
+        self
+    }
+
+    pub fn min_dis(mut self, min_dis: bool) -> Self {
+        self.options.min_dis = Some(min_dis);
+        self
+    }
+
+    pub fn random_order(mut self, random_order: bool) -> Self {
+        self.options.random_order = Some(random_order);
+        self
+    }
+
+    pub fn max_pts(mut self, max_pts: u32) -> Self {
+        self.options.max_pts = Some(max_pts);
+        self
+    }
+
+    pub fn before_pts(mut self, before_pts: u32) -> Self {
+        self.options.before_pts = Some(before_pts);
+        self
+    }
+
+    pub fn after_pts(mut self, after_pts: u32) -> Self {
+        self.options.after_pts = Some(after_pts);
+        self
+    }
+
+    pub fn bonus(mut self, bonus: f64) -> Self {
+        self.options.bonus = Some(bonus);
+        self
+    }
+
+    pub fn build(self) -> TaskOptions {
+        self.options
+    }
+}
+
+fn parse_time(s: &str) -> Result<CupTime, Error> {
+    s.parse().map_err(|error| ParseIssue::new(error).into())
 }
 
 /// Observation zone definition for task points
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObservationZone {
     /// Consecutive number of a waypoint (0 = Start)
     pub index: u32,
@@ -54,20 +249,170 @@ pub struct ObservationZone {
     pub style: ObsZoneStyle,
     /// Radius 1
     pub r1: Option<Distance>,
-    /// Angle 1 in degrees
-    pub a1: Option<f64>,
+    /// Half-angle 1
+    pub a1: Option<HalfAngle>,
     /// Radius 2
     pub r2: Option<Distance>,
-    /// Angle 2 in degrees
-    pub a2: Option<f64>,
-    /// Angle 12
-    pub a12: Option<f64>,
+    /// Half-angle 2
+    pub a2: Option<HalfAngle>,
+    /// Bearing of the zone for [`ObsZoneStyle::Fixed`]
+    pub a12: Option<Degrees>,
     /// Whether zone is a line
     pub line: Option<bool>,
 }
 
+impl ObservationZone {
+    /// Creates a cylinder of the given radius around the task point at index 0
+    pub fn cylinder(radius: Distance) -> Self {
+        Self {
+            index: 0,
+            style: ObsZoneStyle::Symmetrical,
+            r1: Some(radius),
+            a1: Some(HalfAngle::new(180.0)),
+            r2: None,
+            a2: None,
+            a12: None,
+            line: None,
+        }
+    }
+
+    /// Creates a line extending `half_width` to each side of the task point
+    /// at index 0, perpendicular to the bisector of the adjacent legs
+    pub fn line(half_width: Distance) -> Self {
+        Self {
+            index: 0,
+            style: ObsZoneStyle::Symmetrical,
+            r1: Some(half_width),
+            a1: None,
+            r2: None,
+            a2: None,
+            a12: None,
+            line: Some(true),
+        }
+    }
+
+    /// Returns `true` if the zone is a line, in which case `r1` is half its length
+    pub fn is_line(&self) -> bool {
+        self.line == Some(true)
+    }
+
+    /// Returns `true` if the zone is a full circle of radius `r1`
+    ///
+    /// SeeYou writes cylinders with an angle `A1` of 180 degrees, which makes
+    /// the orientation irrelevant. A missing angle is treated the same way.
+    pub fn is_cylinder(&self) -> bool {
+        !self.is_line()
+            && self.r1.is_some()
+            && self.a1.is_none_or(|a1| a1.value() >= 180.0)
+            && self.r2.as_ref().is_none_or(|r2| r2.to_meters() == 0.0)
+    }
+
+    /// Returns `true` if the zone is a sector of radius `r1` and half angle
+    /// `a1` below 180 degrees, optionally combined with a second sector
+    /// defined by `r2` and `a2`
+    pub fn is_sector(&self) -> bool {
+        !self.is_line() && self.r1.is_some() && self.a1.is_some_and(|a1| a1.value() < 180.0)
+    }
+
+    /// Returns the geometry of the zone if it is a [sector](Self::is_sector())
+    ///
+    /// The center bearing is taken from `A12`, defaulting to 0 degrees. For
+    /// zones not oriented [`Fixed`](ObsZoneStyle::Fixed) it depends on the
+    /// course and should be computed from the adjacent task points instead.
+    pub fn as_sector(&self) -> Option<Sector> {
+        if !self.is_sector() {
+            return None;
+        }
+
+        Some(Sector {
+            center_bearing: self.a12.map(f64::from).unwrap_or_default(),
+            half_angle: self.a1?.value(),
+            inner_radius: self.r2.clone().filter(|r2| r2.to_meters() > 0.0),
+            outer_radius: self.r1.clone()?,
+        })
+    }
+
+    /// Returns `r1`, or the default radius for the role of the zone within
+    /// `task` if the file omits it
+    ///
+    /// The zone at index 0 is the start zone, the zone of the last point of
+    /// the [task](Task::waypoint_names) is the finish zone, and all other
+    /// zones are turnpoints.
+    pub fn effective_r1(&self, task: &Task, defaults: &ZoneDefaults) -> Distance {
+        if let Some(r1) = &self.r1 {
+            return r1.clone();
+        }
+
+        let is_last = self.index as usize + 1 == task.waypoint_names.len();
+        let default = if self.index == 0 {
+            &defaults.start
+        } else if is_last {
+            &defaults.finish
+        } else {
+            &defaults.turnpoint
+        };
+        default.clone()
+    }
+}
+
+/// Sector geometry of an observation zone, see [`ObservationZone::as_sector()`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sector {
+    /// Bearing in degrees from the task point to the center of the sector
+    pub center_bearing: f64,
+    /// Angle in degrees to each side of the center bearing
+    pub half_angle: f64,
+    /// Radius of the second zone (`R2`), if any
+    pub inner_radius: Option<Distance>,
+    /// Radius of the sector (`R1`)
+    pub outer_radius: Distance,
+}
+
+/// A leg between two consecutive task points, see [`CupFile::task_legs()`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Leg {
+    /// Index of the task point the leg starts at
+    pub from: usize,
+    /// Index of the task point the leg ends at
+    pub to: usize,
+    /// Great-circle distance of the leg in meters
+    pub distance_m: f64,
+    /// Initial true bearing of the leg in degrees `[0, 360)`
+    pub bearing_deg: f64,
+}
+
+/// Default radii for observation zones without `R1`, see
+/// [`ObservationZone::effective_r1()`]
+///
+/// The [`Default`] values are a 5 km start radius, 500 m turnpoint cylinders,
+/// and a 3 km finish radius. Applications should set them to the defaults of
+/// their own task settings.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneDefaults {
+    /// Radius of start zones
+    pub start: Distance,
+    /// Radius of turnpoint zones
+    pub turnpoint: Distance,
+    /// Radius of finish zones
+    pub finish: Distance,
+}
+
+impl Default for ZoneDefaults {
+    fn default() -> Self {
+        Self {
+            start: Distance::Kilometers(5.0),
+            turnpoint: Distance::Meters(500.0),
+            finish: Distance::Kilometers(3.0),
+        }
+    }
+}
+
 /// Observation zone direction style
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ObsZoneStyle {
     Fixed = 0,
     Symmetrical = 1,
@@ -77,6 +422,17 @@ pub enum ObsZoneStyle {
 }
 
 impl ObsZoneStyle {
+    /// Returns all styles, ordered by their numeric code
+    pub fn all() -> &'static [ObsZoneStyle] {
+        &[
+            ObsZoneStyle::Fixed,
+            ObsZoneStyle::Symmetrical,
+            ObsZoneStyle::ToNextPoint,
+            ObsZoneStyle::ToPreviousPoint,
+            ObsZoneStyle::ToStartPoint,
+        ]
+    }
+
     pub fn from_u8(value: u8) -> Option<Self> {
         match value {
             0 => Some(ObsZoneStyle::Fixed),
@@ -88,3 +444,13 @@ impl ObsZoneStyle {
         }
     }
 }
+
+impl TryFrom<u8> for ObsZoneStyle {
+    type Error = u8;
+
+    /// Returns the style for a numeric code, or the code itself if it is
+    /// out of range
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::from_u8(value).ok_or(value)
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Time of day or duration in `HH:MM:SS` notation, as used by the task options
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CupTime {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
+impl CupTime {
+    /// Returns the time as a duration since midnight
+    pub fn as_duration(&self) -> Duration {
+        let seconds = self.hours as u64 * 3600 + self.minutes as u64 * 60 + self.seconds as u64;
+        Duration::from_secs(seconds)
+    }
+}
+
+impl Display for CupTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            self.hours, self.minutes, self.seconds
+        )
+    }
+}
+
+impl FromStr for CupTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid time: '{s}' (expected HH:MM:SS)");
+
+        let mut parts = s.trim().split(':');
+        let mut next_part = || -> Result<u8, String> {
+            let part = parts.next().ok_or_else(invalid)?;
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            part.parse().map_err(|_| invalid())
+        };
+
+        let hours = next_part()?;
+        let minutes = next_part()?;
+        let seconds = next_part()?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        if minutes >= 60 || seconds >= 60 {
+            return Err(format!("Time out of range: '{s}'"));
+        }
+
+        Ok(CupTime {
+            hours,
+            minutes,
+            seconds,
+        })
+    }
+}
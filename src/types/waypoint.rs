@@ -1,7 +1,14 @@
+use crate::error::ParseIssue;
+use crate::geo;
+use crate::writer;
 use crate::{Elevation, RunwayDimension};
+use std::fmt::{Display, Formatter};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
 
 /// Waypoint information from a CUP file
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Waypoint {
     /// Waypoint name
     pub name: String,
@@ -31,31 +38,486 @@ pub struct Waypoint {
     pub userdata: String,
     /// Picture filenames (stored in pics/ folder of pics.zip)
     pub pictures: Vec<String>,
+    /// Values of unrecognized columns as `(header, value)` pairs, in file order
+    pub extra: Vec<(String, String)>,
+}
+
+impl Waypoint {
+    /// Returns the elevation in meters
+    pub fn elevation_meters(&self) -> f64 {
+        self.elevation.to_meters()
+    }
+
+    /// Returns the elevation in feet
+    pub fn elevation_feet(&self) -> f64 {
+        self.elevation.to_feet()
+    }
+
+    /// Sets the elevation to `meters`, replacing the previous unit
+    pub fn set_elevation_meters(&mut self, meters: f64) {
+        self.elevation = Elevation::Meters(meters);
+    }
+
+    /// Checks that the latitude is within `[-90, 90]` and the longitude
+    /// within `[-180, 180]`
+    pub fn validate_coordinates(&self) -> Result<(), ParseIssue> {
+        check_latitude(self.latitude)?;
+        check_longitude(self.longitude)
+    }
+
+    /// Sets the latitude in decimal degrees, or returns an error if it's not
+    /// within `[-90, 90]`
+    pub fn set_latitude(&mut self, latitude: f64) -> Result<(), ParseIssue> {
+        check_latitude(latitude)?;
+        self.latitude = latitude;
+        Ok(())
+    }
+
+    /// Sets the longitude in decimal degrees, or returns an error if it's not
+    /// within `[-180, 180]`
+    pub fn set_longitude(&mut self, longitude: f64) -> Result<(), ParseIssue> {
+        check_longitude(longitude)?;
+        self.longitude = longitude;
+        Ok(())
+    }
+
+    /// Returns the radio frequency in MHz, or `None` if the `frequency` field
+    /// is empty or not a number
+    ///
+    /// Both `.` and `,` are accepted as decimal separator, e.g. `123.5`,
+    /// `123.500`, and `123,5` all return `123.5`.
+    pub fn frequency_mhz(&self) -> Option<f64> {
+        let frequency = self.frequency.trim().replace(',', ".");
+        let mhz = frequency.parse::<f64>().ok()?;
+        (mhz.is_finite() && mhz > 0.0).then_some(mhz)
+    }
+
+    /// Sets the radio frequency to `mhz`, formatted with three decimals, e.g.
+    /// `123.500`
+    pub fn set_frequency_mhz(&mut self, mhz: f64) {
+        self.frequency = format!("{mhz:.3}");
+    }
+
+    /// Returns the great-circle distance to `other` in meters
+    pub fn distance_to(&self, other: &Waypoint) -> f64 {
+        geo::haversine_distance(
+            self.latitude,
+            self.longitude,
+            other.latitude,
+            other.longitude,
+        )
+    }
+
+    /// Returns the initial great-circle bearing to `other` in degrees `[0, 360)`
+    pub fn bearing_to(&self, other: &Waypoint) -> f64 {
+        geo::initial_bearing(
+            self.latitude,
+            self.longitude,
+            other.latitude,
+            other.longitude,
+        )
+    }
+
+    /// Returns the latitude in CUP notation with three decimals, e.g. `5049.383N`
+    ///
+    /// ```
+    /// # use seeyou_cup::CupFile;
+    /// let input = "name,code,country,lat,lon,elev,style\n\
+    ///              Aachen,AACHEN,DE,5049.383N,00611.183E,189m,2\n";
+    /// let (cup, _) = CupFile::from_str(input).unwrap();
+    /// let waypoint = &cup.waypoints[0];
+    ///
+    /// assert_eq!(waypoint.latitude_cup_string(), "5049.383N");
+    /// assert_eq!(waypoint.longitude_cup_string(), "00611.183E");
+    /// ```
+    pub fn latitude_cup_string(&self) -> String {
+        writer::format_latitude(self.latitude, 3)
+    }
+
+    /// Returns the longitude in CUP notation with three decimals, e.g. `00611.183E`
+    pub fn longitude_cup_string(&self) -> String {
+        writer::format_longitude(self.longitude, 3)
+    }
+
+    /// Returns the position as a [`geo_types::Coord`]
+    ///
+    /// Following the `geo` conventions, `x` is the longitude and `y` is the
+    /// latitude.
+    ///
+    /// ```
+    /// # use seeyou_cup::CupFile;
+    /// let input = "name,code,country,lat,lon,elev,style\n\
+    ///              Aachen,AACHEN,DE,5049.383N,00611.183E,189m,2\n";
+    /// let (cup, _) = CupFile::from_str(input).unwrap();
+    /// let waypoint = &cup.waypoints[0];
+    ///
+    /// let coord = waypoint.as_coord();
+    /// assert_eq!(coord.x, waypoint.longitude);
+    /// assert_eq!(coord.y, waypoint.latitude);
+    ///
+    /// let point = geo_types::Point::from(waypoint);
+    /// assert_eq!(point.x(), waypoint.longitude);
+    /// assert_eq!(point.y(), waypoint.latitude);
+    /// ```
+    #[cfg(feature = "geo-types")]
+    pub fn as_coord(&self) -> geo_types::Coord<f64> {
+        geo_types::coord! { x: self.longitude, y: self.latitude }
+    }
+
+    /// Resolves the picture references against a directory, e.g. the
+    /// extracted `pics` folder of a `.cupx` archive
+    ///
+    /// Both `/` and `\` are accepted as separators of subfolders, e.g.
+    /// `sub/lesce.jpg` or `sub\lesce.jpg`.
+    ///
+    /// Since the references come from the file content, references that
+    /// could point outside of `base`, i.e. containing `..` or a drive prefix
+    /// like `C:`, are skipped. Leading separators are ignored, so `/a.jpg`
+    /// resolves to `a.jpg` within `base` as well.
+    pub fn picture_paths(&self, base: &Path) -> Vec<PathBuf> {
+        let paths = self.pictures.iter().filter_map(|picture| {
+            let components = picture.split(['/', '\\']);
+            let components = components.filter(|component| !matches!(*component, "" | "."));
+
+            let mut path = base.to_path_buf();
+            for component in components {
+                let mut parts = Path::new(component).components();
+                if !matches!(
+                    (parts.next(), parts.next()),
+                    (Some(Component::Normal(_)), None)
+                ) {
+                    return None;
+                }
+                path.push(component);
+            }
+            Some(path)
+        });
+
+        paths.collect()
+    }
+
+    /// Returns the value of an unrecognized column, or an empty string if
+    /// the waypoint has no value for it.
+    pub(crate) fn extra_value(&self, column: &str) -> &str {
+        let value = self.extra.iter().find(|(header, _)| header == column);
+        value.map(|(_, value)| value.as_str()).unwrap_or_default()
+    }
+}
+
+fn check_latitude(latitude: f64) -> Result<(), ParseIssue> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        let message = format!("Latitude out of range: '{latitude}' (must be between -90 and 90)");
+        return Err(ParseIssue::new(message));
+    }
+    Ok(())
+}
+
+fn check_longitude(longitude: f64) -> Result<(), ParseIssue> {
+    if !(-180.0..=180.0).contains(&longitude) {
+        let message =
+            format!("Longitude out of range: '{longitude}' (must be between -180 and 180)");
+        return Err(ParseIssue::new(message));
+    }
+    Ok(())
+}
+
+/// Converts the position into a point with the longitude as `x` and the
+/// latitude as `y`
+#[cfg(feature = "geo-types")]
+impl From<&Waypoint> for geo_types::Point<f64> {
+    fn from(waypoint: &Waypoint) -> Self {
+        waypoint.as_coord().into()
+    }
 }
 
 /// Waypoint style/type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum WaypointStyle {
-    Unknown = 0,
-    Waypoint = 1,
-    GrassAirfield = 2,
-    Outlanding = 3,
-    GlidingAirfield = 4,
-    SolidAirfield = 5,
-    MountainPass = 6,
-    MountainTop = 7,
-    TransmitterMast = 8,
-    Vor = 9,
-    Ndb = 10,
-    CoolingTower = 11,
-    Dam = 12,
-    Tunnel = 13,
-    Bridge = 14,
-    PowerPlant = 15,
-    Castle = 16,
-    Intersection = 17,
-    Marker = 18,
-    ControlPoint = 19,
-    PgTakeOff = 20,
-    PgLandingZone = 21,
+    Unknown,
+    Waypoint,
+    GrassAirfield,
+    Outlanding,
+    GlidingAirfield,
+    SolidAirfield,
+    MountainPass,
+    MountainTop,
+    TransmitterMast,
+    Vor,
+    Ndb,
+    CoolingTower,
+    Dam,
+    Tunnel,
+    Bridge,
+    PowerPlant,
+    Castle,
+    Intersection,
+    Marker,
+    ControlPoint,
+    PgTakeOff,
+    PgLandingZone,
+    /// Style code not known to this library, retained so that it can be
+    /// written back unchanged
+    Other(u8),
+}
+
+impl WaypointStyle {
+    /// Returns all styles defined by the specification, ordered by their
+    /// numeric code. [`WaypointStyle::Other`] is not included.
+    pub fn all() -> &'static [WaypointStyle] {
+        &[
+            WaypointStyle::Unknown,
+            WaypointStyle::Waypoint,
+            WaypointStyle::GrassAirfield,
+            WaypointStyle::Outlanding,
+            WaypointStyle::GlidingAirfield,
+            WaypointStyle::SolidAirfield,
+            WaypointStyle::MountainPass,
+            WaypointStyle::MountainTop,
+            WaypointStyle::TransmitterMast,
+            WaypointStyle::Vor,
+            WaypointStyle::Ndb,
+            WaypointStyle::CoolingTower,
+            WaypointStyle::Dam,
+            WaypointStyle::Tunnel,
+            WaypointStyle::Bridge,
+            WaypointStyle::PowerPlant,
+            WaypointStyle::Castle,
+            WaypointStyle::Intersection,
+            WaypointStyle::Marker,
+            WaypointStyle::ControlPoint,
+            WaypointStyle::PgTakeOff,
+            WaypointStyle::PgLandingZone,
+        ]
+    }
+
+    /// Returns the known style for a numeric code, or `None` if the code
+    /// isn't one of the styles `0..=21` defined by the specification.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(WaypointStyle::Unknown),
+            1 => Some(WaypointStyle::Waypoint),
+            2 => Some(WaypointStyle::GrassAirfield),
+            3 => Some(WaypointStyle::Outlanding),
+            4 => Some(WaypointStyle::GlidingAirfield),
+            5 => Some(WaypointStyle::SolidAirfield),
+            6 => Some(WaypointStyle::MountainPass),
+            7 => Some(WaypointStyle::MountainTop),
+            8 => Some(WaypointStyle::TransmitterMast),
+            9 => Some(WaypointStyle::Vor),
+            10 => Some(WaypointStyle::Ndb),
+            11 => Some(WaypointStyle::CoolingTower),
+            12 => Some(WaypointStyle::Dam),
+            13 => Some(WaypointStyle::Tunnel),
+            14 => Some(WaypointStyle::Bridge),
+            15 => Some(WaypointStyle::PowerPlant),
+            16 => Some(WaypointStyle::Castle),
+            17 => Some(WaypointStyle::Intersection),
+            18 => Some(WaypointStyle::Marker),
+            19 => Some(WaypointStyle::ControlPoint),
+            20 => Some(WaypointStyle::PgTakeOff),
+            21 => Some(WaypointStyle::PgLandingZone),
+            _ => None,
+        }
+    }
+
+    /// Numeric style code as used in CUP files
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            WaypointStyle::Unknown => 0,
+            WaypointStyle::Waypoint => 1,
+            WaypointStyle::GrassAirfield => 2,
+            WaypointStyle::Outlanding => 3,
+            WaypointStyle::GlidingAirfield => 4,
+            WaypointStyle::SolidAirfield => 5,
+            WaypointStyle::MountainPass => 6,
+            WaypointStyle::MountainTop => 7,
+            WaypointStyle::TransmitterMast => 8,
+            WaypointStyle::Vor => 9,
+            WaypointStyle::Ndb => 10,
+            WaypointStyle::CoolingTower => 11,
+            WaypointStyle::Dam => 12,
+            WaypointStyle::Tunnel => 13,
+            WaypointStyle::Bridge => 14,
+            WaypointStyle::PowerPlant => 15,
+            WaypointStyle::Castle => 16,
+            WaypointStyle::Intersection => 17,
+            WaypointStyle::Marker => 18,
+            WaypointStyle::ControlPoint => 19,
+            WaypointStyle::PgTakeOff => 20,
+            WaypointStyle::PgLandingZone => 21,
+            WaypointStyle::Other(value) => *value,
+        }
+    }
+
+    /// Returns `true` for styles a glider can land at, i.e. airfields and
+    /// outlanding fields
+    pub fn is_landable(&self) -> bool {
+        self.is_airfield() || *self == WaypointStyle::Outlanding
+    }
+
+    /// Returns `true` for grass, gliding, and solid airfields
+    pub fn is_airfield(&self) -> bool {
+        matches!(
+            self,
+            WaypointStyle::GrassAirfield
+                | WaypointStyle::GlidingAirfield
+                | WaypointStyle::SolidAirfield
+        )
+    }
+
+    /// Returns `true` for radio navigation aids, i.e. VOR and NDB
+    pub fn is_navaid(&self) -> bool {
+        matches!(self, WaypointStyle::Vor | WaypointStyle::Ndb)
+    }
+
+    /// Returns the high-level category of the style, e.g. for map legends
+    pub fn category(&self) -> WaypointCategory {
+        match self {
+            WaypointStyle::GrassAirfield
+            | WaypointStyle::Outlanding
+            | WaypointStyle::GlidingAirfield
+            | WaypointStyle::SolidAirfield => WaypointCategory::Airfield,
+            WaypointStyle::PgLandingZone => WaypointCategory::Landable,
+            WaypointStyle::Vor | WaypointStyle::Ndb => WaypointCategory::Navaid,
+            WaypointStyle::MountainPass
+            | WaypointStyle::MountainTop
+            | WaypointStyle::TransmitterMast
+            | WaypointStyle::CoolingTower
+            | WaypointStyle::Dam
+            | WaypointStyle::Tunnel
+            | WaypointStyle::Bridge
+            | WaypointStyle::PowerPlant
+            | WaypointStyle::Castle
+            | WaypointStyle::Intersection => WaypointCategory::Landmark,
+            WaypointStyle::Unknown
+            | WaypointStyle::Waypoint
+            | WaypointStyle::Marker
+            | WaypointStyle::ControlPoint
+            | WaypointStyle::PgTakeOff
+            | WaypointStyle::Other(_) => WaypointCategory::Other,
+        }
+    }
+
+    /// Human-readable name of the style, e.g. "Solid Airfield"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WaypointStyle::Unknown => "Unknown",
+            WaypointStyle::Waypoint => "Waypoint",
+            WaypointStyle::GrassAirfield => "Grass Airfield",
+            WaypointStyle::Outlanding => "Outlanding",
+            WaypointStyle::GlidingAirfield => "Gliding Airfield",
+            WaypointStyle::SolidAirfield => "Solid Airfield",
+            WaypointStyle::MountainPass => "Mountain Pass",
+            WaypointStyle::MountainTop => "Mountain Top",
+            WaypointStyle::TransmitterMast => "Transmitter Mast",
+            WaypointStyle::Vor => "VOR",
+            WaypointStyle::Ndb => "NDB",
+            WaypointStyle::CoolingTower => "Cooling Tower",
+            WaypointStyle::Dam => "Dam",
+            WaypointStyle::Tunnel => "Tunnel",
+            WaypointStyle::Bridge => "Bridge",
+            WaypointStyle::PowerPlant => "Power Plant",
+            WaypointStyle::Castle => "Castle",
+            WaypointStyle::Intersection => "Intersection",
+            WaypointStyle::Marker => "Marker",
+            WaypointStyle::ControlPoint => "Control Point",
+            WaypointStyle::PgTakeOff => "PG Take Off",
+            WaypointStyle::PgLandingZone => "PG Landing Zone",
+            WaypointStyle::Other(_) => "Other",
+        }
+    }
+
+    /// Stable machine-readable key of the style, e.g. `"solid_airfield"`
+    ///
+    /// Unlike [`as_str()`](Self::as_str), the keys are not meant for display
+    /// and won't change, so they can be used as icon filename stems.
+    pub fn symbol_key(&self) -> &'static str {
+        match self {
+            WaypointStyle::Unknown => "unknown",
+            WaypointStyle::Waypoint => "waypoint",
+            WaypointStyle::GrassAirfield => "grass_airfield",
+            WaypointStyle::Outlanding => "outlanding",
+            WaypointStyle::GlidingAirfield => "gliding_airfield",
+            WaypointStyle::SolidAirfield => "solid_airfield",
+            WaypointStyle::MountainPass => "mountain_pass",
+            WaypointStyle::MountainTop => "mountain_top",
+            WaypointStyle::TransmitterMast => "transmitter_mast",
+            WaypointStyle::Vor => "vor",
+            WaypointStyle::Ndb => "ndb",
+            WaypointStyle::CoolingTower => "cooling_tower",
+            WaypointStyle::Dam => "dam",
+            WaypointStyle::Tunnel => "tunnel",
+            WaypointStyle::Bridge => "bridge",
+            WaypointStyle::PowerPlant => "power_plant",
+            WaypointStyle::Castle => "castle",
+            WaypointStyle::Intersection => "intersection",
+            WaypointStyle::Marker => "marker",
+            WaypointStyle::ControlPoint => "control_point",
+            WaypointStyle::PgTakeOff => "pg_take_off",
+            WaypointStyle::PgLandingZone => "pg_landing_zone",
+            WaypointStyle::Other(_) => "other",
+        }
+    }
+}
+
+/// High-level grouping of [`WaypointStyle`]s, see [`WaypointStyle::category()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum WaypointCategory {
+    /// Grass, gliding, and solid airfields and outlanding fields, i.e. all
+    /// [landable](WaypointStyle::is_landable) styles
+    Airfield,
+    /// Paragliding landing zones, which aren't
+    /// [landable](WaypointStyle::is_landable) for gliders
+    Landable,
+    /// Radio navigation aids, i.e. VOR and NDB
+    Navaid,
+    /// Terrain features and structures visible from the air, e.g. mountain
+    /// tops, dams, or castles
+    Landmark,
+    /// Generic waypoints and all remaining styles
+    Other,
+}
+
+impl Display for WaypointStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WaypointStyle {
+    type Err = String;
+
+    /// Parses either a human-readable name (case-insensitive) or a numeric style code
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let style = match s.parse::<u8>() {
+            Ok(value) => Self::from_u8(value),
+            Err(_) => (0..=u8::MAX)
+                .map_while(Self::from_u8)
+                .find(|style| style.as_str().eq_ignore_ascii_case(s)),
+        };
+
+        style.ok_or_else(|| format!("Invalid waypoint style: '{s}'"))
+    }
+}
+
+impl TryFrom<u8> for WaypointStyle {
+    type Error = u8;
+
+    /// Returns the known style for a numeric code, or the code itself if it
+    /// isn't one of the styles `0..=21` defined by the specification
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::from_u8(value).ok_or(value)
+    }
 }
@@ -1,15 +1,198 @@
-pub fn format_latitude(lat: f64) -> String {
+use std::borrow::Cow;
+use std::fmt;
+
+/// Precision limit for the minutes, beyond which `f64` has no meaningful digits left
+const MAX_DECIMALS: u8 = 9;
+
+pub fn format_latitude(lat: f64, decimals: u8) -> String {
+    let mut output = String::new();
+    // Writing into a `String` can't fail
+    write_latitude(&mut output, lat, decimals).expect("formatting latitude failed");
+    output
+}
+
+pub fn format_longitude(lon: f64, decimals: u8) -> String {
+    let mut output = String::new();
+    // Writing into a `String` can't fail
+    write_longitude(&mut output, lon, decimals).expect("formatting longitude failed");
+    output
+}
+
+/// Writes a latitude in `DDMM.mmmN` notation without intermediate allocations
+pub fn write_latitude<W: fmt::Write>(w: &mut W, lat: f64, decimals: u8) -> fmt::Result {
     let hemisphere = if lat >= 0.0 { 'N' } else { 'S' };
-    let abs_lat = lat.abs();
-    let degrees = abs_lat.floor() as u32;
-    let minutes = (abs_lat - degrees as f64) * 60.0;
-    format!("{:02}{:06.3}{}", degrees, minutes, hemisphere)
+    write_degrees_and_minutes(w, lat.abs(), decimals, 2)?;
+    w.write_char(hemisphere)
 }
 
-pub fn format_longitude(lon: f64) -> String {
+/// Writes a longitude in `DDDMM.mmmE` notation without intermediate allocations
+pub fn write_longitude<W: fmt::Write>(w: &mut W, lon: f64, decimals: u8) -> fmt::Result {
     let hemisphere = if lon >= 0.0 { 'E' } else { 'W' };
-    let abs_lon = lon.abs();
-    let degrees = abs_lon.floor() as u32;
-    let minutes = (abs_lon - degrees as f64) * 60.0;
-    format!("{:03}{:06.3}{}", degrees, minutes, hemisphere)
+    write_degrees_and_minutes(w, lon.abs(), decimals, 3)?;
+    w.write_char(hemisphere)
+}
+
+/// Formats an optional runway direction, zero-padded to three digits if `pad` is set
+pub fn format_runway_direction(direction: Option<u16>, pad: bool) -> String {
+    match direction {
+        Some(direction) if pad => format!("{direction:03}"),
+        Some(direction) => direction.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Joins picture filenames with `;`, escaping `;` within each filename
+///
+/// A `\` is only escaped where it would otherwise be read as an escape, i.e.
+/// before `;` or `\` and at the end of a filename, so that paths like
+/// `sub\a.jpg` are written unchanged.
+pub fn format_pictures(pictures: &[String]) -> String {
+    let pictures = pictures.iter().map(|picture| {
+        let mut escaped = String::with_capacity(picture.len());
+        let mut chars = picture.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                ';' => escaped.push_str("\\;"),
+                '\\' if matches!(chars.peek(), None | Some(';' | '\\')) => escaped.push_str("\\\\"),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    });
+    pictures.collect::<Vec<_>>().join(";")
+}
+
+/// Indices of the waypoint columns that SeeYou always quotes: `name`,
+/// `code`, `desc`, and `pics`
+const TEXT_COLUMNS: [usize; 4] = [0, 1, 11, 13];
+
+/// Quotes the [text columns](TEXT_COLUMNS) of a waypoint record, and all
+/// other fields only if necessary
+///
+/// The CSV writer can only quote either all fields or the necessary ones, so
+/// the result is meant for a writer that doesn't quote by itself.
+pub fn quote_fields<'a>(fields: impl IntoIterator<Item = &'a str>) -> Vec<Cow<'a, str>> {
+    let fields = fields.into_iter().enumerate().map(|(idx, field)| {
+        let needs_quotes = field.contains([',', '"', '\r', '\n']);
+        if TEXT_COLUMNS.contains(&idx) || needs_quotes {
+            Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+        } else {
+            Cow::Borrowed(field)
+        }
+    });
+
+    fields.collect()
+}
+
+/// Writes an absolute coordinate as whole degrees, padded to `degree_digits`,
+/// followed by the decimal minutes.
+///
+/// The value is rounded to the requested precision *before* splitting, so
+/// that e.g. 59.9999' carries over into the degrees instead of producing an
+/// invalid `60.000` minutes value.
+fn write_degrees_and_minutes<W: fmt::Write>(
+    w: &mut W,
+    value: f64,
+    decimals: u8,
+    degree_digits: usize,
+) -> fmt::Result {
+    let decimals = decimals.min(MAX_DECIMALS) as usize;
+    let scale = 10u64.pow(decimals as u32);
+
+    let total = (value * 60.0 * scale as f64).round() as u64;
+    let degrees = total / (60 * scale);
+    let minutes = total % (60 * scale);
+
+    let whole = minutes / scale;
+    let fraction = minutes % scale;
+    write!(w, "{degrees:0degree_digits$}{whole:02}")?;
+    if decimals > 0 {
+        write!(w, ".{fraction:0decimals$}")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::basics::{parse_latitude, parse_longitude};
+    use claims::assert_ok;
+    use proptest::proptest;
+
+    #[test]
+    fn test_format_latitude() {
+        assert_eq!(format_latitude(51.7968166, 3), "5147.809N");
+        assert_eq!(format_latitude(-51.7968166, 3), "5147.809S");
+        assert_eq!(format_latitude(0.0, 3), "0000.000N");
+        assert_eq!(format_latitude(90.0, 3), "9000.000N");
+        assert_eq!(format_latitude(-90.0, 3), "9000.000S");
+        assert_eq!(format_latitude(51.7968166, 5), "5147.80900N");
+        assert_eq!(format_latitude(51.7968166, 1), "5147.8N");
+    }
+
+    #[test]
+    fn test_format_longitude() {
+        assert_eq!(format_longitude(14.17445, 3), "01410.467E");
+        assert_eq!(format_longitude(-4.0833833, 3), "00405.003W");
+        assert_eq!(format_longitude(180.0, 3), "18000.000E");
+        assert_eq!(format_longitude(-180.0, 3), "18000.000W");
+        assert_eq!(format_longitude(14.17445, 5), "01410.46700E");
+    }
+
+    #[test]
+    fn test_minutes_carry_over() {
+        assert_eq!(format_latitude(10.9999999, 3), "1100.000N");
+        assert_eq!(format_latitude(-10.9999999, 3), "1100.000S");
+        assert_eq!(format_longitude(10.9999999, 3), "01100.000E");
+        assert_eq!(format_latitude(10.9999999, 5), "1059.99999N");
+        assert_eq!(format_latitude(10.99999999, 5), "1100.00000N");
+    }
+
+    #[test]
+    fn test_latitude_roundtrip_proptest() {
+        proptest!(|(lat in -90.0..=90.0f64, decimals in 3..=9u8)| {
+            let formatted = format_latitude(lat, decimals);
+            let parsed = parse_latitude(&formatted).unwrap();
+            assert!((parsed - lat).abs() < 0.0001, "{lat} -> {formatted} -> {parsed}");
+        });
+    }
+
+    #[test]
+    fn test_longitude_roundtrip_proptest() {
+        proptest!(|(lon in -180.0..=180.0f64, decimals in 3..=9u8)| {
+            let formatted = format_longitude(lon, decimals);
+            let parsed = parse_longitude(&formatted).unwrap();
+            assert!((parsed - lon).abs() < 0.0001, "{lon} -> {formatted} -> {parsed}");
+        });
+    }
+
+    #[test]
+    fn test_write_coordinates() {
+        let mut output = String::from("lat=");
+        assert_ok!(write_latitude(&mut output, 51.7968166, 3));
+        output.push_str(" lon=");
+        assert_ok!(write_longitude(&mut output, -4.0833833, 3));
+        assert_eq!(output, "lat=5147.809N lon=00405.003W");
+
+        let mut output = String::new();
+        assert_ok!(write_latitude(&mut output, 10.9999999, 0));
+        assert_ok!(write_longitude(&mut output, 180.0, 12));
+        assert_eq!(output, "1100N18000.000000000E");
+    }
+
+    #[test]
+    fn test_format_pictures() {
+        let pictures = [
+            "a.jpg".to_string(),
+            "my;photo.jpg".to_string(),
+            r"b\c.jpg".to_string(),
+            r"d\".to_string(),
+        ];
+        assert_eq!(
+            format_pictures(&pictures),
+            r"a.jpg;my\;photo.jpg;b\c.jpg;d\\"
+        );
+        assert_eq!(format_pictures(&[]), "");
+    }
 }
@@ -0,0 +1,124 @@
+use crate::error::Error;
+use crate::{Encoding, WriteOptions};
+use encoding_rs::{EncoderResult, ISO_8859_2, UTF_8, WINDOWS_1250, WINDOWS_1252};
+use std::io::{self, BufWriter, Write};
+
+/// Encodes the UTF-8 output on the fly, without buffering the whole file
+///
+/// The encoded output is buffered in a [`BufWriter`], since it is written in
+/// many small fragments.
+///
+/// Line endings at the end of the written data are held back until more
+/// data follows, so that [`finish()`](Self::finish) can normalize the end
+/// of the file.
+pub struct EncodingWriter<W: Write> {
+    writer: BufWriter<W>,
+    encoding: Encoding,
+    encoder: encoding_rs::Encoder,
+    /// UTF-8 data that hasn't been encoded yet, i.e. trailing line endings
+    /// and incomplete characters
+    pending: Vec<u8>,
+    unmappable: bool,
+}
+
+impl<W: Write> EncodingWriter<W> {
+    pub fn new(writer: W, encoding: Encoding) -> Self {
+        let encoding_impl = match encoding {
+            Encoding::Utf8 => UTF_8,
+            Encoding::Windows1252 => WINDOWS_1252,
+            Encoding::Latin2 => ISO_8859_2,
+            Encoding::Windows1250 => WINDOWS_1250,
+        };
+
+        Self {
+            writer: BufWriter::new(writer),
+            encoding,
+            encoder: encoding_impl.new_encoder(),
+            pending: Vec::new(),
+            unmappable: false,
+        }
+    }
+
+    /// Ends the output with a single line ending, or none at all if
+    /// [`WriteOptions::trailing_newline`] is disabled
+    ///
+    /// `result` is the result of writing the output, whose I/O error is
+    /// replaced by an encoding error if a character couldn't be encoded.
+    pub fn finish(
+        mut self,
+        result: Result<(), Error>,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        if self.unmappable {
+            return Err(Error::Encoding(format!(
+                "Failed to encode with {:?}",
+                self.encoding
+            )));
+        }
+        result?;
+
+        self.pending.clear();
+        if options.trailing_newline {
+            let line_ending = options.line_ending.as_str();
+            self.pending.extend_from_slice(line_ending.as_bytes());
+        }
+        self.encode(self.pending.len(), true)?;
+
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Encodes the first `len` bytes of the pending data
+    fn encode(&mut self, len: usize, last: bool) -> io::Result<()> {
+        // Only ever called with complete characters
+        let mut src = std::str::from_utf8(&self.pending[..len]).map_err(io::Error::other)?;
+
+        let mut buffer = [0; 4096];
+        loop {
+            let (result, read, written) =
+                self.encoder
+                    .encode_from_utf8_without_replacement(src, &mut buffer, last);
+            self.writer.write_all(&buffer[..written])?;
+            src = &src[read..];
+
+            match result {
+                EncoderResult::InputEmpty => break,
+                EncoderResult::OutputFull => continue,
+                EncoderResult::Unmappable(c) => {
+                    self.unmappable = true;
+                    let message = format!("Unmappable character: '{c}'");
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                }
+            }
+        }
+
+        self.pending.drain(..len);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncodingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        let complete_len = match std::str::from_utf8(&self.pending) {
+            Ok(s) => s.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+        };
+
+        let complete = &self.pending[..complete_len];
+        let trailing_line_endings = complete
+            .iter()
+            .rev()
+            .take_while(|b| matches!(b, b'\r' | b'\n'))
+            .count();
+
+        self.encode(complete_len - trailing_line_endings, false)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
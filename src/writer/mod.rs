@@ -1,66 +1,170 @@
 mod basics;
+mod encoding;
 mod task;
 mod waypoint;
 
-use crate::Encoding;
-use crate::CupFile;
 use crate::error::Error;
+use crate::spec::{COLUMNS, TASK_SEPARATOR};
+pub(crate) use crate::writer::basics::{format_latitude, format_longitude};
+use crate::writer::encoding::EncodingWriter;
 use crate::writer::task::format_task;
 use crate::writer::waypoint::write_waypoint;
-use csv::Writer;
-use encoding_rs::{Encoding as EncodingImpl, UTF_8, WINDOWS_1252};
+use crate::{CupFile, Encoding, LineEnding, Waypoint, WriteOptions};
+use csv::{QuoteStyle, Terminator, Writer, WriterBuilder};
 use std::io::Write;
 
-pub fn write<W: Write>(
-    cup_file: &CupFile,
-    mut writer: W,
-    encoding: Encoding,
+pub fn write<W: Write>(cup_file: &CupFile, writer: W, options: &WriteOptions) -> Result<(), Error> {
+    let extra_columns = extra_columns(cup_file);
+
+    let mut output = EncodingWriter::new(writer, options.encoding);
+    let result =
+        write_waypoint_section(&mut output, cup_file, &extra_columns, options).and_then(|_| {
+            // Files without tasks end after the waypoints
+            if cup_file.tasks.is_empty() {
+                return Ok(());
+            }
+            write_task_section(&mut output, cup_file, &extra_columns, options)
+        });
+    output.finish(result, options)
+}
+
+/// Writes a single waypoint row, including the values of its own extra
+/// columns in their original order
+pub fn write_single_waypoint<W: Write>(
+    waypoint: &Waypoint,
+    writer: W,
+    options: &WriteOptions,
 ) -> Result<(), Error> {
-    let content = format_cup_file(cup_file)?;
+    let extra_columns = waypoint.extra.iter().map(|(header, _)| header.as_str());
+    let extra_columns = extra_columns.collect::<Vec<_>>();
 
-    let encoding_impl: &'static EncodingImpl = match encoding {
-        Encoding::Utf8 => UTF_8,
-        Encoding::Windows1252 => WINDOWS_1252,
+    let mut output = EncodingWriter::new(writer, options.encoding);
+    let mut csv_writer = waypoint_csv_writer(&mut output, options);
+    let result = write_waypoint(&mut csv_writer, waypoint, &extra_columns, options)
+        .and_then(|_| Ok(csv_writer.flush()?));
+    drop(csv_writer);
+    output.finish(result, options)
+}
+
+pub(crate) fn format_cup_file(cup_file: &CupFile, options: &WriteOptions) -> Result<String, Error> {
+    let options = WriteOptions {
+        encoding: Encoding::Utf8,
+        ..options.clone()
     };
 
-    let (encoded_bytes, _, had_errors) = encoding_impl.encode(&content);
-    if had_errors {
-        return Err(Error::Encoding(format!(
-            "Failed to encode with {:?}",
-            encoding
-        )));
-    }
+    let mut output = Vec::new();
+    write(cup_file, &mut output, &options)?;
+    String::from_utf8(output).map_err(|e| Error::Encoding(e.to_string()))
+}
 
-    writer.write_all(&encoded_bytes)?;
-    Ok(())
+/// Writes the header row and waypoints, without the task section
+pub fn write_waypoints<W: Write>(
+    cup_file: &CupFile,
+    writer: W,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let extra_columns = extra_columns(cup_file);
+
+    let mut output = EncodingWriter::new(writer, options.encoding);
+    let result = write_waypoint_section(&mut output, cup_file, &extra_columns, options);
+    output.finish(result, options)
 }
 
-fn format_cup_file(cup_file: &CupFile) -> Result<String, Error> {
-    let mut output = Vec::new();
-    let mut csv_writer = Writer::from_writer(&mut output);
+/// Writes the task separator and tasks, without the header row and waypoints
+pub fn write_tasks<W: Write>(
+    cup_file: &CupFile,
+    writer: W,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let extra_columns = extra_columns(cup_file);
 
-    csv_writer.write_record([
-        "name", "code", "country", "lat", "lon", "elev", "style", "rwdir", "rwlen", "rwwidth",
-        "freq", "desc", "userdata", "pics",
-    ])?;
+    let mut output = EncodingWriter::new(writer, options.encoding);
+    let result = write_task_section(&mut output, cup_file, &extra_columns, options);
+    output.finish(result, options)
+}
 
-    for waypoint in &cup_file.waypoints {
-        write_waypoint(&mut csv_writer, waypoint)?;
+fn write_waypoint_section<W: Write>(
+    output: &mut W,
+    cup_file: &CupFile,
+    extra_columns: &[&str],
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let mut csv_writer = csv_writer(&mut *output, options);
+
+    if let Some(comment) = &options.header_comment {
+        csv_writer.write_record([comment])?;
     }
 
+    let columns = COLUMNS.into_iter().chain(extra_columns.iter().copied());
+    csv_writer.write_record(columns)?;
     csv_writer.flush()?;
     drop(csv_writer);
 
-    let mut result = String::from_utf8(output).map_err(|e| Error::Encoding(e.to_string()))?;
+    let mut csv_writer = waypoint_csv_writer(output, options);
+    for waypoint in &cup_file.waypoints {
+        write_waypoint(&mut csv_writer, waypoint, extra_columns, options)?;
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+/// Writes the task separator and tasks, or only the separator if there are
+/// no tasks
+fn write_task_section<W: Write>(
+    output: &mut W,
+    cup_file: &CupFile,
+    extra_columns: &[&str],
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let line_ending = options.line_ending.as_str();
+    write!(output, "{TASK_SEPARATOR}{line_ending}")?;
+
+    for task in &cup_file.tasks {
+        let task = format_task(task, extra_columns, options)?;
+        write!(output, "{task}{line_ending}")?;
+    }
 
-    if !cup_file.tasks.is_empty() {
-        result.push_str("-----Related Tasks-----\n");
+    Ok(())
+}
+
+/// Creates a CSV writer using the configured line terminator
+fn csv_writer<W: Write>(writer: W, options: &WriteOptions) -> Writer<W> {
+    csv_writer_builder(options).from_writer(writer)
+}
+
+/// Creates a CSV writer for waypoint records, which quotes nothing by itself
+/// if [`WriteOptions::always_quote_text_fields`] is set, since the fields are
+/// then quoted by [`basics::quote_fields()`] instead
+fn waypoint_csv_writer<W: Write>(writer: W, options: &WriteOptions) -> Writer<W> {
+    let mut builder = csv_writer_builder(options);
+    if options.always_quote_text_fields {
+        builder.quote_style(QuoteStyle::Never);
+    }
+    builder.from_writer(writer)
+}
+
+fn csv_writer_builder(options: &WriteOptions) -> WriterBuilder {
+    let terminator = match options.line_ending {
+        LineEnding::Lf => Terminator::Any(b'\n'),
+        LineEnding::Crlf => Terminator::CRLF,
+    };
+
+    let mut builder = WriterBuilder::new();
+    builder.flexible(true).terminator(terminator);
+    builder
+}
 
-        for task in &cup_file.tasks {
-            result.push_str(&format_task(task)?);
-            result.push('\n');
+/// Collects the names of all unrecognized columns in order of first appearance
+fn extra_columns(cup_file: &CupFile) -> Vec<&str> {
+    let mut columns = Vec::new();
+    for waypoint in cup_file.all_waypoints() {
+        for (header, _) in &waypoint.extra {
+            if !columns.contains(&header.as_str()) {
+                columns.push(header.as_str());
+            }
         }
     }
 
-    Ok(result)
+    columns
 }
@@ -1,14 +1,20 @@
-use crate::writer::basics;
-use crate::{Error, ObservationZone, Task, TaskOptions, Waypoint};
-use csv::Writer;
-
-pub fn format_task(task: &Task) -> Result<String, Error> {
+use crate::spec::{OBS_ZONE, OPTIONS, POINT, STARTS};
+use crate::writer::{basics, csv_writer, csv_writer_builder, waypoint_csv_writer};
+use crate::{Error, ObservationZone, Task, TaskOptions, Waypoint, WriteOptions};
+use csv::QuoteStyle;
+
+pub fn format_task(
+    task: &Task,
+    extra_columns: &[&str],
+    options: &WriteOptions,
+) -> Result<String, Error> {
     let mut result = String::new();
+    let line_ending = options.line_ending.as_str();
 
     // Write the task line with waypoint names
     {
         let mut output = Vec::new();
-        let mut csv_writer = Writer::from_writer(&mut output);
+        let mut csv_writer = csv_writer(&mut output, options);
 
         let mut record = vec![task.description.as_deref().unwrap_or("").to_string()];
 
@@ -27,33 +33,38 @@ pub fn format_task(task: &Task) -> Result<String, Error> {
 
     // Write task options if present
     if let Some(options) = &task.options {
-        result.push('\n');
+        result.push_str(line_ending);
         result.push_str(&format_task_options(options)?);
     }
 
     // Write observation zones
     for obs_zone in &task.observation_zones {
-        result.push('\n');
+        result.push_str(line_ending);
         result.push_str(&format_observation_zone(obs_zone)?);
     }
 
     // Write inline waypoints as separate Point= lines
     for (idx, waypoint) in &task.points {
-        result.push('\n');
-        result.push_str(&format_inline_waypoint_line(*idx as usize, waypoint)?);
+        result.push_str(line_ending);
+        result.push_str(&format_inline_waypoint_line(
+            *idx as usize,
+            waypoint,
+            extra_columns,
+            options,
+        )?);
     }
 
     // Write multiple starts if present
     if !task.multiple_starts.is_empty() {
-        result.push('\n');
-        result.push_str(&format_multiple_starts(&task.multiple_starts)?);
+        result.push_str(line_ending);
+        result.push_str(&format_multiple_starts(&task.multiple_starts, options)?);
     }
 
     Ok(result)
 }
 
 fn format_task_options(options: &TaskOptions) -> Result<String, Error> {
-    let mut parts = vec!["Options".to_string()];
+    let mut parts = vec![OPTIONS.to_string()];
 
     if let Some(no_start) = &options.no_start {
         parts.push(format!("NoStart={}", no_start));
@@ -91,13 +102,16 @@ fn format_task_options(options: &TaskOptions) -> Result<String, Error> {
     if let Some(bonus) = options.bonus {
         parts.push(format!("Bonus={}", bonus));
     }
+    for (key, value) in &options.extra_options {
+        parts.push(format!("{key}={value}"));
+    }
 
     Ok(parts.join(","))
 }
 
 fn format_observation_zone(obs_zone: &ObservationZone) -> Result<String, Error> {
     let mut parts = vec![
-        format!("ObsZone={}", obs_zone.index),
+        format!("{OBS_ZONE}={}", obs_zone.index),
         format!("Style={}", obs_zone.style as u8),
     ];
 
@@ -123,37 +137,48 @@ fn format_observation_zone(obs_zone: &ObservationZone) -> Result<String, Error>
     Ok(parts.join(","))
 }
 
-fn format_multiple_starts(starts: &[String]) -> Result<String, Error> {
+fn format_multiple_starts(starts: &[String], options: &WriteOptions) -> Result<String, Error> {
     // Format: STARTS="Start1","Start2","Start3"
-    let quoted_starts: Vec<String> = starts.iter().map(|s| format!("\"{}\"", s)).collect();
-    Ok(format!("STARTS={}", quoted_starts.join(",")))
+    let mut output = Vec::new();
+    let mut builder = csv_writer_builder(options);
+    let mut csv_writer = builder
+        .quote_style(QuoteStyle::Always)
+        .from_writer(&mut output);
+
+    csv_writer.write_record(starts)?;
+    csv_writer.flush()?;
+    drop(csv_writer);
+
+    let starts = String::from_utf8(output).map_err(|e| Error::Encoding(e.to_string()))?;
+    Ok(format!("{STARTS}={}", starts.trim_end()))
 }
 
-fn format_inline_waypoint_line(index: usize, waypoint: &Waypoint) -> Result<String, Error> {
+fn format_inline_waypoint_line(
+    index: usize,
+    waypoint: &Waypoint,
+    extra_columns: &[&str],
+    options: &WriteOptions,
+) -> Result<String, Error> {
     // Format: Point=1,"Point_3",PNT_3,,4627.136N,01412.856E,0.0m,1,,,,,,,
-    let pics = if waypoint.pictures.is_empty() {
-        String::new()
-    } else {
-        waypoint.pictures.join(";")
-    };
+    let pics = basics::format_pictures(&waypoint.pictures);
 
     // Create a CSV writer to properly format the waypoint data
     let mut output = Vec::new();
     {
-        let mut csv_writer = Writer::from_writer(&mut output);
-        csv_writer.write_record([
-            &format!("Point={}", index),
+        let mut csv_writer = waypoint_csv_writer(&mut output, options);
+        let fields = [
+            &format!("{POINT}={index}"),
             &waypoint.name,
             &waypoint.code,
             &waypoint.country,
-            &basics::format_latitude(waypoint.latitude),
-            &basics::format_longitude(waypoint.longitude),
+            &basics::format_latitude(waypoint.latitude, options.coordinate_decimals),
+            &basics::format_longitude(waypoint.longitude, options.coordinate_decimals),
             &waypoint.elevation.to_string(),
-            &(waypoint.style as u8).to_string(),
-            &waypoint
-                .runway_direction
-                .map(|d| format!("{:03}", d))
-                .unwrap_or_default(),
+            &waypoint.style.as_u8().to_string(),
+            &basics::format_runway_direction(
+                waypoint.runway_direction,
+                options.pad_runway_direction,
+            ),
             &waypoint
                 .runway_length
                 .as_ref()
@@ -168,7 +193,21 @@ fn format_inline_waypoint_line(index: usize, waypoint: &Waypoint) -> Result<Stri
             &waypoint.description,
             &waypoint.userdata,
             &pics,
-        ])?;
+        ];
+
+        let mut fields = fields.into_iter().map(String::as_str);
+        let extra_values = extra_columns
+            .iter()
+            .map(|column| waypoint.extra_value(column));
+
+        if options.always_quote_text_fields {
+            let point = fields.next().unwrap_or_default();
+            let fields = basics::quote_fields(fields.chain(extra_values));
+            let fields = fields.iter().map(|field| field.as_ref());
+            csv_writer.write_record(std::iter::once(point).chain(fields))?;
+        } else {
+            csv_writer.write_record(fields.chain(extra_values))?;
+        }
         csv_writer.flush()?;
     }
 
@@ -1,29 +1,34 @@
-use crate::writer::basics::{format_latitude, format_longitude};
-use crate::{Error, Waypoint};
+use crate::writer::basics::{
+    format_pictures, format_runway_direction, quote_fields, write_latitude, write_longitude,
+};
+use crate::{Error, Waypoint, WriteOptions};
 use csv::Writer;
 
 pub fn write_waypoint<W: std::io::Write>(
     writer: &mut Writer<W>,
     waypoint: &Waypoint,
+    extra_columns: &[&str],
+    options: &WriteOptions,
 ) -> Result<(), Error> {
-    let pics = if waypoint.pictures.is_empty() {
-        String::new()
-    } else {
-        waypoint.pictures.join(";")
-    };
+    let pics = format_pictures(&waypoint.pictures);
+
+    // Both coordinates share a single buffer to avoid allocations per field
+    let mut coordinates = String::with_capacity(32);
+    let decimals = options.coordinate_decimals;
+    write_latitude(&mut coordinates, waypoint.latitude, decimals).expect("formatting failed");
+    let latitude_len = coordinates.len();
+    write_longitude(&mut coordinates, waypoint.longitude, decimals).expect("formatting failed");
+    let (latitude, longitude) = coordinates.split_at(latitude_len);
 
-    writer.write_record([
+    let fields = [
         &waypoint.name,
         &waypoint.code,
         &waypoint.country,
-        &format_latitude(waypoint.latitude),
-        &format_longitude(waypoint.longitude),
+        latitude,
+        longitude,
         &waypoint.elevation.to_string(),
-        &(waypoint.style as u8).to_string(),
-        &waypoint
-            .runway_direction
-            .map(|d| format!("{:03}", d))
-            .unwrap_or_default(),
+        &waypoint.style.as_u8().to_string(),
+        &format_runway_direction(waypoint.runway_direction, options.pad_runway_direction),
         &waypoint
             .runway_length
             .as_ref()
@@ -38,7 +43,19 @@ pub fn write_waypoint<W: std::io::Write>(
         &waypoint.description,
         &waypoint.userdata,
         &pics,
-    ])?;
+    ];
+
+    let extra_values = extra_columns
+        .iter()
+        .map(|column| waypoint.extra_value(column));
+    let fields = fields.into_iter().chain(extra_values);
+
+    if options.always_quote_text_fields {
+        let fields = quote_fields(fields);
+        writer.write_record(fields.iter().map(|field| field.as_bytes()))?;
+    } else {
+        writer.write_record(fields)?;
+    }
 
     Ok(())
 }
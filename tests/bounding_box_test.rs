@@ -0,0 +1,72 @@
+use claims::{assert_none, assert_ok, assert_some};
+use seeyou_cup::CupFile;
+
+#[test]
+fn test_empty() {
+    let cup = CupFile::default();
+    assert_none!(cup.bounding_box());
+}
+
+#[test]
+fn test_single_waypoint() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"A",A,XX,4500.000N,01000.000E,0m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let bbox = assert_some!(cup.bounding_box());
+    assert_eq!(bbox.min_lat, 45.0);
+    assert_eq!(bbox.max_lat, 45.0);
+    assert_eq!(bbox.min_lon, 10.0);
+    assert_eq!(bbox.max_lon, 10.0);
+    assert!(!bbox.crosses_antimeridian);
+}
+
+#[test]
+fn test_regular() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"A",A,XX,4500.000N,01000.000E,0m,1
+"B",B,XX,4630.000N,00500.000W,0m,1
+"C",C,XX,4400.000N,01200.000E,0m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let bbox = assert_some!(cup.bounding_box());
+    assert_eq!(bbox.min_lat, 44.0);
+    assert_eq!(bbox.max_lat, 46.5);
+    assert_eq!(bbox.min_lon, -5.0);
+    assert_eq!(bbox.max_lon, 12.0);
+    assert!(!bbox.crosses_antimeridian);
+}
+
+#[test]
+fn test_straddling_antimeridian() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Fiji",FJ,FJ,1800.000S,17800.000E,0m,1
+"Samoa",WS,WS,1400.000S,17200.000W,0m,1
+"Tonga",TO,TO,2100.000S,17500.000W,0m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let bbox = assert_some!(cup.bounding_box());
+    assert_eq!(bbox.min_lat, -21.0);
+    assert_eq!(bbox.max_lat, -14.0);
+    assert_eq!(bbox.min_lon, 178.0);
+    assert_eq!(bbox.max_lon, -172.0);
+    assert!(bbox.crosses_antimeridian);
+}
+
+#[test]
+fn test_wide_spread_does_not_cross_antimeridian() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"A",A,XX,0000.000N,10000.000W,0m,1
+"B",B,XX,0000.000N,00000.000E,0m,1
+"C",C,XX,0000.000N,10000.000E,0m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let bbox = assert_some!(cup.bounding_box());
+    assert_eq!(bbox.min_lon, -100.0);
+    assert_eq!(bbox.max_lon, 100.0);
+    assert!(!bbox.crosses_antimeridian);
+}
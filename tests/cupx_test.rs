@@ -0,0 +1,116 @@
+#![cfg(feature = "cupx")]
+
+use claims::{assert_err, assert_none, assert_ok, assert_some};
+use insta::assert_snapshot;
+use seeyou_cup::CupFile;
+use std::io::Write;
+use std::path::PathBuf;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+const CUP: &str = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+"Aachen",AACHEN,DE,5049.383N,00611.183E,189m,2,,,,,,,"aachen.jpg;tower.jpg"
+"Düren",DUEREN,DE,5048.000N,00629.000E,150m,1,,,,,,,"missing.png"
+"#;
+
+fn write_archive(name: &str, entries: &[(&str, &[u8])]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("seeyou-cup-{}-{name}", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (name, content) in entries {
+        zip.start_file(*name, options).unwrap();
+        zip.write_all(content).unwrap();
+    }
+    zip.finish().unwrap();
+
+    path
+}
+
+#[test]
+fn test_read_cupx() {
+    let path = write_archive(
+        "valid.cupx",
+        &[
+            ("POINTS.CUP", CUP.as_bytes()),
+            ("pics/aachen.jpg", b"aachen"),
+            ("Pics/Tower.JPG", b"tower"),
+            ("pics/unused.jpg", b"unused"),
+        ],
+    );
+
+    let (cup, mut pictures, warnings) = assert_ok!(CupFile::from_cupx_path(&path));
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(warnings.len(), 0);
+    assert_eq!(cup.waypoints.len(), 2);
+    assert_eq!(cup.waypoints[1].name, "Düren");
+
+    let names: Vec<_> = pictures.picture_names().collect();
+    assert_eq!(names, ["aachen.jpg", "Tower.JPG", "unused.jpg"]);
+
+    assert!(pictures.contains("aachen.jpg"));
+    assert!(pictures.contains("tower.jpg"));
+    assert!(!pictures.contains("missing.png"));
+
+    let bytes = assert_some!(assert_ok!(pictures.picture("aachen.jpg")));
+    assert_eq!(bytes, b"aachen");
+    let bytes = assert_some!(assert_ok!(pictures.picture("tower.jpg")));
+    assert_eq!(bytes, b"tower");
+    assert_none!(assert_ok!(pictures.picture("missing.png")));
+
+    assert_eq!(pictures.missing_pictures(&cup), ["missing.png"]);
+}
+
+#[test]
+fn test_missing_pictures_of_inline_waypoints() {
+    let cup = format!(
+        "{CUP}-----Related Tasks-----\n\
+        ,Aachen,Inline,Aachen\n\
+        Point=1,Inline,INL,DE,5050.000N,00620.000E,200m,1,,,,,,,\"inline.jpg;aachen.jpg\"\n"
+    );
+    let path = write_archive(
+        "inline.cupx",
+        &[
+            ("POINTS.CUP", cup.as_bytes()),
+            ("pics/aachen.jpg", b"aachen"),
+        ],
+    );
+
+    let (cup, pictures, warnings) = assert_ok!(CupFile::from_cupx_path(&path));
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(warnings.len(), 0);
+    let missing = pictures.missing_pictures(&cup);
+    assert_eq!(missing, ["tower.jpg", "missing.png", "inline.jpg"]);
+}
+
+#[test]
+fn test_no_cup_entry() {
+    let path = write_archive("no-cup.cupx", &[("pics/aachen.jpg", b"aachen")]);
+    let result = CupFile::from_cupx_path(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let err = assert_err!(result);
+    assert_snapshot!(err, @"Parse error: No .cup file found in archive");
+}
+
+#[test]
+fn test_multiple_cup_entries() {
+    let path = write_archive(
+        "multiple-cup.cupx",
+        &[("a.cup", CUP.as_bytes()), ("b.cup", CUP.as_bytes())],
+    );
+    let result = CupFile::from_cupx_path(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let err = assert_err!(result);
+    assert_snapshot!(err, @"Parse error: Multiple .cup files found in archive");
+}
+
+#[test]
+fn test_not_a_zip_file() {
+    let err = assert_err!(CupFile::from_cupx_path("tests/fixtures/EC25.cup"));
+    assert!(matches!(err, seeyou_cup::Error::Zip(_)), "{err:?}");
+}
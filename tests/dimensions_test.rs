@@ -0,0 +1,145 @@
+use claims::{assert_none, assert_ok, assert_some_eq};
+use seeyou_cup::{CupFile, Distance, ElevUnit, Elevation, RunwayDimension, RunwayUnit};
+use std::cmp::Ordering;
+
+#[test]
+fn test_distance_ordering_across_units() {
+    assert!(Distance::Kilometers(1.0) < Distance::Meters(2000.0));
+    assert!(Distance::Meters(2000.0) > Distance::Kilometers(1.0));
+    assert!(Distance::NauticalMiles(1.0) > Distance::StatuteMiles(1.0));
+    assert!(Distance::StatuteMiles(1.0) > Distance::Kilometers(1.6));
+    assert!(Distance::Meters(500.0) <= Distance::Meters(500.0));
+}
+
+#[test]
+fn test_distance_cmp_meters() {
+    let a = Distance::Kilometers(1.0);
+    let b = Distance::Meters(1000.0);
+    assert_some_eq!(a.cmp_meters(&b), Ordering::Equal);
+
+    // equally long, but not equal according to `PartialEq`
+    assert_ne!(a, b);
+    assert_none!(a.partial_cmp(&b));
+}
+
+#[test]
+fn test_runway_dimension_ordering_across_units() {
+    assert!(RunwayDimension::NauticalMiles(1.0) > RunwayDimension::Meters(1800.0));
+    assert!(RunwayDimension::StatuteMiles(1.0) < RunwayDimension::Meters(1700.0));
+}
+
+#[test]
+fn test_elevation_ordering_across_units() {
+    assert!(Elevation::Feet(1000.0) < Elevation::Meters(500.0));
+    assert!(Elevation::Feet(2000.0) > Elevation::Meters(500.0));
+    assert_some_eq!(
+        Elevation::Feet(1000.0).cmp_meters(&Elevation::Meters(304.8)),
+        Ordering::Equal
+    );
+}
+
+#[test]
+fn test_constructors() {
+    assert_eq!(Elevation::from_meters(504.0), Elevation::Meters(504.0));
+    assert_eq!(Elevation::from_feet(1000.0), Elevation::Feet(1000.0));
+
+    let rwlen = RunwayDimension::from_meters(1130.0);
+    assert_eq!(rwlen, RunwayDimension::Meters(1130.0));
+    let rwlen = RunwayDimension::from_nautical_miles(0.5);
+    assert_eq!(rwlen, RunwayDimension::NauticalMiles(0.5));
+    let rwlen = RunwayDimension::from_statute_miles(0.5);
+    assert_eq!(rwlen, RunwayDimension::StatuteMiles(0.5));
+
+    assert_eq!(Distance::from_meters(500.0), Distance::Meters(500.0));
+    assert_eq!(Distance::from_kilometers(5.0), Distance::Kilometers(5.0));
+    assert_eq!(
+        Distance::from_nautical_miles(2.0),
+        Distance::NauticalMiles(2.0)
+    );
+    assert_eq!(
+        Distance::from_statute_miles(2.0),
+        Distance::StatuteMiles(2.0)
+    );
+}
+
+#[test]
+fn test_elevation_arithmetic() {
+    assert_eq!(
+        Elevation::Meters(300.0) + Elevation::Meters(50.0),
+        Elevation::Meters(350.0)
+    );
+    assert_eq!(
+        Elevation::Feet(1000.0) - Elevation::Feet(200.0),
+        Elevation::Feet(800.0)
+    );
+
+    // mixed units are converted to meters
+    assert_eq!(
+        Elevation::Meters(300.0) + Elevation::Feet(100.0),
+        Elevation::Meters(330.48)
+    );
+    assert_eq!(
+        Elevation::Feet(1000.0) - Elevation::Meters(100.0),
+        Elevation::Meters(204.8)
+    );
+}
+
+#[test]
+fn test_distance_scaling() {
+    assert_eq!(Distance::Kilometers(0.5) * 2.0, Distance::Kilometers(1.0));
+    assert_eq!(Distance::Meters(400.0) * 1.5, Distance::Meters(600.0));
+    assert_eq!(
+        Distance::NauticalMiles(3.0) / 2.0,
+        Distance::NauticalMiles(1.5)
+    );
+    assert_eq!(
+        Distance::StatuteMiles(10.0) / 4.0,
+        Distance::StatuteMiles(2.5)
+    );
+}
+
+#[test]
+fn test_to_unit() {
+    assert_eq!(
+        Elevation::Feet(1000.0).to_unit(ElevUnit::Meters),
+        Elevation::Meters(304.8)
+    );
+    assert_eq!(
+        Elevation::Feet(1000.0).to_unit(ElevUnit::Feet),
+        Elevation::Feet(1000.0)
+    );
+    assert_eq!(
+        RunwayDimension::NauticalMiles(0.5).to_unit(RunwayUnit::Meters),
+        RunwayDimension::Meters(926.0)
+    );
+    assert_eq!(
+        RunwayDimension::Meters(1852.0).to_unit(RunwayUnit::NauticalMiles),
+        RunwayDimension::NauticalMiles(1.0)
+    );
+}
+
+#[test]
+fn test_normalize_units() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth
+"Cross Hands","CSS",UK,5147.809N,00405.003W,1000ft,5,090,0.5nm,100.0m
+-----Related Tasks-----
+"Task","Cross Hands","Turnpoint"
+Options,NearDis=1km,NearAlt=500ft
+Point=1,"Turnpoint",TP,UK,5148.000N,00406.000W,2000ft,1,,,
+"#;
+
+    let (mut cup, _) = assert_ok!(CupFile::from_str(input));
+    cup.normalize_units(ElevUnit::Meters, RunwayUnit::Meters);
+
+    let waypoint = &cup.waypoints[0];
+    assert_eq!(waypoint.elevation, Elevation::Meters(304.8));
+    assert_eq!(waypoint.runway_length, Some(RunwayDimension::Meters(926.0)));
+    assert_eq!(waypoint.runway_width, Some(RunwayDimension::Meters(100.0)));
+
+    let task = &cup.tasks[0];
+    assert_eq!(task.points[0].1.elevation, Elevation::Meters(609.6));
+
+    let options = task.options.as_ref().unwrap();
+    assert_eq!(options.near_dis, Some(Distance::Meters(1000.0)));
+    assert_eq!(options.near_alt, Some(Elevation::Meters(152.4)));
+}
@@ -0,0 +1,76 @@
+use claims::assert_ok;
+use seeyou_cup::CupFile;
+
+#[test]
+fn test_duplicate_names() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Aachen",AACHEN,DE,5049.383N,00611.183E,189m,2
+"Düren",DUEREN,DE,5048.000N,00629.000E,150m,1
+"Aachen",AACHEN2,DE,5049.000N,00612.000E,200m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.duplicate_names(), vec![("Aachen", vec![0, 2])]);
+}
+
+#[test]
+fn test_no_duplicate_names() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Aachen",AACHEN,DE,5049.383N,00611.183E,189m,2
+"aachen",AACHEN2,DE,5049.000N,00612.000E,200m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.duplicate_names(), vec![]);
+}
+
+#[test]
+fn test_deduplicate_by_coordinate() {
+    // "Aachen" and "Aachen Merzbrueck" are about 5m apart, "Dueren" is far away
+    let input = r#"name,code,country,lat,lon,elev,style
+"Aachen",,DE,5049.383N,00611.183E,189m,1
+"Dueren",DUEREN,DE,5048.000N,00629.000E,150m,1
+"Aachen Merzbrueck",EDKA,DE,5049.386N,00611.183E,189m,5
+"Aachen TP",,DE,5049.384N,00611.184E,189m,1
+"#;
+
+    let (mut cup, _) = assert_ok!(CupFile::from_str(input));
+    let distance = cup.waypoints[0].distance_to(&cup.waypoints[2]);
+    assert!((distance - 5.6).abs() < 0.1, "{distance}");
+
+    let removed = cup.deduplicate_by_coordinate(10.0);
+    assert_eq!(removed, vec![0, 3]);
+
+    let names = cup.waypoints.iter().map(|wp| wp.name.as_str());
+    assert_eq!(
+        names.collect::<Vec<_>>(),
+        vec!["Dueren", "Aachen Merzbrueck"]
+    );
+
+    let removed = cup.deduplicate_by_coordinate(1.0);
+    assert_eq!(removed, Vec::<usize>::new());
+}
+
+#[test]
+fn test_build_indices() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Lesce",LJBL,SI,4621.379N,01410.467E,504m,5
+"Bovec",,SI,4619.850N,01332.950E,443m,2
+"Lesce",LESCE,SI,4621.400N,01410.500E,504m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.duplicate_names(), vec![("Lesce", vec![0, 2])]);
+
+    let names = cup.build_name_index();
+    assert_eq!(names.len(), 2);
+    assert_eq!(names.get("Lesce"), Some(&2));
+    assert_eq!(names.get("Bovec"), Some(&1));
+    assert_eq!(names.get("Unknown"), None);
+
+    let codes = cup.build_code_index();
+    assert_eq!(codes.len(), 2);
+    assert_eq!(codes.get("LJBL"), Some(&0));
+    assert_eq!(codes.get("LESCE"), Some(&2));
+    assert_eq!(codes.get(""), None);
+}
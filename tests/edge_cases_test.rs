@@ -1,5 +1,5 @@
 use claims::{assert_err, assert_ok, assert_some};
-use seeyou_cup::CupFile;
+use seeyou_cup::{CupFile, DecodeErrorPolicy, Elevation, ParseOptions};
 
 #[test]
 fn test_empty_file() {
@@ -60,7 +60,7 @@ fn test_malformed_csv_unclosed_quotes() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '' (expected 9 characters, got 0)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '' (expected 9 characters, got 0)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -71,7 +71,7 @@ fn test_truncated_file_incomplete_row() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid elevation: ''", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid elevation: ''", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -95,6 +95,35 @@ fn test_leading_trailing_whitespace_in_field_values() {
     assert_eq!(cup.waypoints[0].country, "  XX  ");
 }
 
+#[test]
+fn test_trim_fields() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata
+"  Test  ","  T  ","  XX  ",5147.809N,00405.003W,500m,1,,,," 123.500 ","  Desc "," data  "
+"#;
+    let options = ParseOptions {
+        trim_fields: true,
+        ..Default::default()
+    };
+
+    let (cup, _) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    let waypoint = &cup.waypoints[0];
+    assert_eq!(waypoint.name, "Test");
+    assert_eq!(waypoint.code, "T");
+    assert_eq!(waypoint.country, "XX");
+    assert_eq!(waypoint.frequency, "123.500");
+    assert_eq!(waypoint.description, "Desc");
+    assert_eq!(waypoint.userdata, "data");
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let waypoint = &cup.waypoints[0];
+    assert_eq!(waypoint.name, "  Test  ");
+    assert_eq!(waypoint.frequency, " 123.500 ");
+    assert_eq!(waypoint.userdata, " data  ");
+}
+
 #[test]
 fn test_tab_characters_in_csv() {
     // Using tabs as separators (should still work with CSV parser)
@@ -104,6 +133,49 @@ fn test_tab_characters_in_csv() {
     assert!(format!("{}", err).contains("Parse error") || format!("{}", err).contains("Missing"));
 }
 
+#[test]
+fn test_tab_delimiter() {
+    let input = "name\tcode\tcountry\tlat\tlon\telev\tstyle\n\"Test\"\t\"T\"\t\"XX\"\t5147.809N\t00405.003W\t500m\t1\n";
+    let options = ParseOptions {
+        delimiter: b'\t',
+        ..Default::default()
+    };
+
+    let (cup, warnings) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(cup.waypoints.len(), 1);
+    assert_eq!(cup.waypoints[0].name, "Test");
+    assert_eq!(cup.waypoints[0].code, "T");
+    assert_eq!(cup.waypoints[0].elevation, Elevation::Meters(500.0));
+}
+
+#[test]
+fn test_semicolon_delimiter() {
+    let input = r#"name;code;country;lat;lon;elev;style;rwdir;rwlen;rwwidth;freq;desc;userdata;pics
+"Test";"T";"XX";5147.809N;00405.003W;504,5m;5;090;1200m;30m;"123.500";"Comma, in text";;"a.jpg;b.jpg"
+"Other";"O";"XX";5148.000N;00406.000W;600m;1;;;;;;;c.jpg;d.jpg
+-----Related Tasks-----
+"Task";"Test";"Other";"Test"
+ObsZone=1;Style=1;R1=1,5km;A1=45
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Values beyond the header columns: 'd.jpg'", line: Some(3), severity: Warning })]"#);
+    assert_eq!(cup.waypoints.len(), 2);
+    assert_eq!(cup.waypoints[0].elevation, Elevation::Meters(504.5));
+    assert_eq!(cup.waypoints[0].description, "Comma, in text");
+    assert_eq!(cup.waypoints[0].pictures, ["a.jpg", "b.jpg"]);
+    assert_eq!(cup.waypoints[1].pictures, ["c.jpg"]);
+
+    assert_eq!(cup.tasks.len(), 1);
+    assert_eq!(cup.tasks[0].waypoint_names, ["Test", "Other", "Test"]);
+    let r1 = assert_some!(&cup.tasks[0].observation_zones[0].r1);
+    assert_eq!(r1.to_meters(), 1500.0);
+}
+
 #[test]
 fn test_unicode_characters_beyond_ascii() {
     let input = r#"name,code,country,lat,lon,elev,style,desc
@@ -119,14 +191,24 @@ fn test_unicode_characters_beyond_ascii() {
 }
 
 #[test]
-fn test_file_with_only_task_section_should_fail() {
+fn test_file_with_only_task_section_warns() {
     let input = r#"-----Related Tasks-----
 "Task 1","Waypoint1","Waypoint2"
 "#;
-    // This should fail because waypoints are referenced but not defined
-    let err = assert_err!(CupFile::from_str(input));
-    // The exact error depends on how the parser handles missing waypoint section
-    assert!(format!("{}", err).contains("Parse error") || format!("{}", err).contains("Missing"));
+    // Waypoints are referenced but not defined
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.tasks.len(), 1);
+    assert_eq!(warnings.len(), 2);
+
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let err = assert_err!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert!(format!("{}", err).contains("Parse error"));
 }
 
 #[test]
@@ -166,6 +248,89 @@ Point=1,"Inline TP","T1","XX",5148.000N,00406.000W,600m,1
     assert_eq!(cup.tasks[0].points[0].1.name, "Inline TP");
 }
 
+#[test]
+fn test_malformed_inline_waypoint() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S","XX",5147.809N,00405.003W,500m,2
+-----Related Tasks-----
+"Task with inline","Start","","","Start"
+Point=1,"Inline TP","T1","XX",5148.000N,00406.000W,600m,1
+Point=2,"","T2","XX",5148.500N,00406.500W,650m,1
+Point=3,"Other TP","T3","XX",5149.000N,00407.000W,700m,1
+"#;
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.tasks.len(), 1);
+
+    let points = cup.tasks[0].points.iter();
+    let points = points.map(|(idx, wp)| (*idx, wp.name.as_str()));
+    assert_eq!(
+        points.collect::<Vec<_>>(),
+        [(1, "Inline TP"), (3, "Other TP")]
+    );
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Name field cannot be empty", line: Some(6), severity: Error })]"#);
+
+    let options = ParseOptions {
+        skip_invalid_waypoints: false,
+        ..Default::default()
+    };
+    let err = assert_err!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    insta::assert_snapshot!(err, @"Parse error on line 6: Name field cannot be empty");
+}
+
+#[test]
+fn test_duplicate_inline_point_index() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S","XX",5147.809N,00405.003W,500m,2
+-----Related Tasks-----
+"Task with inline","Start","","Start"
+Point=1,"First TP","T1","XX",5148.000N,00406.000W,600m,1
+Point=1,"Second TP","T2","XX",5148.500N,00406.500W,650m,1
+"#;
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped record: Duplicate point index 1", line: Some(6), severity: Error })]"#);
+
+    assert_eq!(cup.tasks[0].points.len(), 1);
+    assert_eq!(cup.tasks[0].points[0].0, 1);
+    assert_eq!(cup.tasks[0].points[0].1.name, "First TP");
+}
+
+#[test]
+fn test_blank_lines_between_task_lines() {
+    let input = "name,code,country,lat,lon,elev,style
+\"Start\",S,XX,5147.809N,00405.003W,500m,2
+\"Finish\",F,XX,5149.000N,00407.000W,700m,2
+-----Related Tasks-----
+\"Task 1\",\"Start\",\"Finish\"
+Options,NoStart=12:00:00
+
+   
+ObsZone=0,Style=2,R1=400m
+,,,
+
+
+\"Task 2\",\"Finish\",\"Start\"
+ObsZone=0,Style=2,R1=400m
+
+
+";
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(warnings.len(), 0);
+    assert_eq!(cup.tasks.len(), 2);
+
+    let task = &cup.tasks[0];
+    assert_eq!(task.description.as_deref(), Some("Task 1"));
+    assert_eq!(task.waypoint_names, vec!["Start", "Finish"]);
+    assert!(task.options.is_some());
+    assert_eq!(task.observation_zones.len(), 1);
+
+    let task = &cup.tasks[1];
+    assert_eq!(task.description.as_deref(), Some("Task 2"));
+    assert_eq!(task.observation_zones.len(), 1);
+}
+
 #[test]
 fn test_preserve_all_task_fields_roundtrip() {
     let input = r#"name,code,country,lat,lon,elev,style
@@ -200,3 +365,68 @@ STARTS="Start","Start2"
     // Check that multiple starts are preserved
     assert!(!task.multiple_starts.is_empty());
 }
+
+#[test]
+fn test_oversized_field() {
+    let long_description = "x".repeat(2000);
+    let input = format!(
+        r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc
+"Good",G,XX,5147.809N,00405.003W,500m,1,,,,,
+"Long",L,XX,5147.809N,00405.003W,500m,1,,,,,{long_description}
+-----Related Tasks-----
+"Task","Good","Good"
+ObsZone=0,Style=2,R1=400m,{long_description}
+"Long Task","Good",{long_description}
+"#
+    );
+    let options = ParseOptions {
+        max_field_length: 1000,
+        ..Default::default()
+    };
+
+    let (cup, warnings) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert_eq!(cup.waypoints.len(), 1);
+    assert_eq!(cup.waypoints[0].name, "Good");
+    assert_eq!(cup.tasks.len(), 1);
+    assert_eq!(cup.tasks[0].observation_zones, vec![]);
+
+    let messages = warnings.iter().map(|w| (w.line(), w.message()));
+    insta::assert_compact_debug_snapshot!(messages.collect::<Vec<_>>(), @r#"[(Some(3), "Skipped record: Field too long: 2000 bytes (must be at most 1000)"), (Some(6), "Skipped record: Field too long: 2000 bytes (must be at most 1000)"), (Some(7), "Skipped record: Field too long: 2000 bytes (must be at most 1000)")]"#);
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(&input));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(cup.waypoints.len(), 2);
+    assert_eq!(cup.tasks.len(), 2);
+}
+
+#[test]
+fn test_error_classification() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Test",T,XX,5147.809N,00405.003W,500m,1
+-----Related Tasks-----
+"Task","Test"
+Options,NearDis=far
+"#;
+    let err = assert_err!(CupFile::from_str(input));
+    assert!(err.is_recoverable());
+    assert_eq!(err.line(), Some(5));
+
+    let err = assert_err!(CupFile::from_str(""));
+    assert!(err.is_recoverable());
+    assert_eq!(err.line(), None);
+
+    let options = ParseOptions {
+        on_decode_error: DecodeErrorPolicy::Fail,
+        ..Default::default()
+    };
+    let err = assert_err!(CupFile::from_bytes_with_options(b"name\xF6", &options));
+    assert!(err.is_recoverable());
+    assert_eq!(err.line(), None);
+
+    let err = assert_err!(CupFile::from_path("tests/fixtures/does-not-exist.cup"));
+    assert!(!err.is_recoverable());
+    assert_eq!(err.line(), None);
+}
@@ -1,7 +1,7 @@
-use claims::assert_ok;
+use claims::{assert_err, assert_ok};
 use insta::assert_snapshot;
 use seeyou_cup::Encoding::{self, Utf8, Windows1252};
-use seeyou_cup::CupFile;
+use seeyou_cup::{CupFile, DecodeErrorPolicy, ParseOptions};
 use std::path::{Path, PathBuf};
 
 const FIXTURES: [(&str, Encoding); 4] = [
@@ -55,6 +55,80 @@ fn test_explicit_windows1252() {
     assert_snapshot!(cup.waypoints[121].description, @"Passhöhe");
 }
 
+#[test]
+fn test_from_bytes() {
+    let bytes = assert_ok!(std::fs::read(hotzenwald()));
+
+    let (cup, _) = assert_ok!(CupFile::from_bytes(&bytes));
+    assert_eq!(cup.waypoints.len(), 252);
+    assert_snapshot!(cup.waypoints[121].description, @"Passhöhe");
+
+    let (expected, _) = assert_ok!(CupFile::from_path(hotzenwald()));
+    assert_eq!(cup, expected);
+
+    let (cup, _) = assert_ok!(CupFile::from_bytes_with_encoding(&bytes, Utf8));
+    assert_eq!(cup.waypoints.len(), 252);
+    assert_snapshot!(cup.waypoints[121].description, @"Passh�he");
+}
+
+#[test]
+fn test_decode_error_policy() {
+    let mut input =
+        "name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc\n".to_string();
+    input.push_str("\"Zürich\",ZUR,CH,4723.033N,00829.967E,408m,5,,,,,\"Flugplatz\"\n");
+    let mut input = input.into_bytes();
+    input.extend_from_slice(b"\"Passh\xF6he\",PASS,CH,4723.033N,00829.967E,408m,1,,,,,\"Pass\"\n");
+
+    let options = |on_decode_error| ParseOptions {
+        encoding: Some(Utf8),
+        on_decode_error,
+        ..Default::default()
+    };
+
+    let (cup, warnings) = assert_ok!(CupFile::from_bytes_with_options(
+        &input,
+        &options(DecodeErrorPolicy::Replace)
+    ));
+    assert_eq!(warnings, vec![]);
+    assert_snapshot!(cup.waypoints[1].name, @"Passh�he");
+
+    let (cup, warnings) = assert_ok!(CupFile::from_bytes_with_options(
+        &input,
+        &options(DecodeErrorPolicy::Warn)
+    ));
+    assert_snapshot!(cup.waypoints[1].name, @"Passh�he");
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Invalid UTF-8 byte sequence at byte offset 134, replaced with U+FFFD", line: Some(3), severity: Warning })]"#);
+
+    let error = assert_err!(CupFile::from_bytes_with_options(
+        &input,
+        &options(DecodeErrorPolicy::Fail)
+    ));
+    assert_snapshot!(error, @"Encoding error: Invalid UTF-8 byte sequence at byte offset 134");
+
+    // Automatic detection falls back to a legacy encoding instead
+    let options = |on_decode_error| ParseOptions {
+        on_decode_error,
+        ..Default::default()
+    };
+
+    let (cup, warnings) = assert_ok!(CupFile::from_bytes(&input));
+    assert_eq!(warnings, vec![]);
+    assert_snapshot!(cup.waypoints[1].name, @"Passhöhe");
+
+    let (cup, warnings) = assert_ok!(CupFile::from_bytes_with_options(
+        &input,
+        &options(DecodeErrorPolicy::Warn)
+    ));
+    assert_snapshot!(cup.waypoints[1].name, @"Passhöhe");
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Invalid UTF-8 byte sequence at byte offset 134, decoded as windows-1252 instead", line: Some(3), severity: Warning })]"#);
+
+    let error = assert_err!(CupFile::from_bytes_with_options(
+        &input,
+        &options(DecodeErrorPolicy::Fail)
+    ));
+    assert_snapshot!(error, @"Encoding error: Invalid UTF-8 byte sequence at byte offset 134");
+}
+
 #[test]
 fn test_all_fixtures_parse() {
     let fixtures_path = Path::new("tests/fixtures");
@@ -74,3 +148,100 @@ fn test_all_fixtures_parse_auto_detect() {
         assert!(!cup.waypoints.is_empty(), "No waypoints in {}", fixture);
     }
 }
+
+#[test]
+fn test_utf8_bom_is_stripped() {
+    let input = "\u{FEFF}name,code,country,lat,lon,elev,style\n\"Zürich\",ZUR,CH,4722.614N,00832.502E,408m,1\n";
+
+    let (cup, warnings) = assert_ok!(CupFile::from_reader(input.as_bytes()));
+    assert_eq!(warnings.len(), 0);
+    assert_eq!(cup.waypoints.len(), 1);
+    assert_eq!(cup.waypoints[0].name, "Zürich");
+
+    let (cup, _) = assert_ok!(CupFile::from_reader_with_encoding(input.as_bytes(), Utf8));
+    assert_eq!(cup.waypoints[0].name, "Zürich");
+
+    let mut bytes = b"\xEF\xBB\xBF".to_vec();
+    bytes.extend(
+        b"name,code,country,lat,lon,elev,style\n\"Z\xFCrich\",ZUR,CH,4722.614N,00832.502E,408m,1\n",
+    );
+    let (cup, _) = assert_ok!(CupFile::from_reader_with_encoding(&bytes[..], Windows1252));
+    assert_eq!(cup.waypoints[0].name, "Zürich");
+}
+
+#[test]
+fn test_utf16_bom_is_rejected() {
+    let input = b"\xFF\xFEn\0a\0m\0e\0";
+    let err = assert_err!(CupFile::from_reader(&input[..]));
+    assert_snapshot!(err, @"Encoding error: UTF-16 encoded files are not supported, please convert to UTF-8");
+
+    let input = b"\xFE\xFF\0n\0a\0m\0e";
+    let err = assert_err!(CupFile::from_reader_with_encoding(&input[..], Utf8));
+    assert_snapshot!(err, @"Encoding error: UTF-16 encoded files are not supported, please convert to UTF-8");
+}
+
+const POLISH: &str = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc
+"Łódź Lublinek",EPLL,PL,5143.317N,01923.883E,184m,5,,,,,"Lotnisko położone w południowo-zachodniej części miasta, niedaleko źródeł rzeki Ner."
+"Żar",EPZR,PL,4947.250N,01912.983E,885m,2,,,,,"Szybowisko na szczycie góry, znane z żaglowania na zboczu."
+"#;
+
+#[test]
+fn test_latin2_roundtrip() {
+    let (cup, _) = assert_ok!(CupFile::from_str(POLISH));
+    let bytes = assert_ok!(cup.to_bytes_with_encoding(Encoding::Latin2));
+    assert!(std::str::from_utf8(&bytes).is_err());
+
+    let (parsed, _) = assert_ok!(CupFile::from_reader_with_encoding(
+        &bytes[..],
+        Encoding::Latin2
+    ));
+    assert_eq!(parsed, cup);
+}
+
+#[cfg(feature = "charset-detect")]
+#[test]
+fn test_charset_detect_latin2() {
+    let (cup, _) = assert_ok!(CupFile::from_str(POLISH));
+    let bytes = assert_ok!(cup.to_bytes_with_encoding(Encoding::Latin2));
+    assert_eq!(Encoding::detect(&bytes), Some(Encoding::Latin2));
+
+    let (parsed, _) = assert_ok!(CupFile::from_reader(&bytes[..]));
+    assert_eq!(parsed.waypoints[0].name, "Łódź Lublinek");
+}
+
+#[cfg(feature = "charset-detect")]
+#[test]
+fn test_charset_detect_fixtures() {
+    for (name, encoding) in FIXTURES {
+        let bytes = assert_ok!(std::fs::read(Path::new("tests/fixtures").join(name)));
+        assert_eq!(Encoding::detect(&bytes), Some(encoding), "{name}");
+    }
+}
+
+#[test]
+fn test_encoding_from_str() {
+    let cases = [
+        ("utf-8", Utf8),
+        ("UTF8", Utf8),
+        ("Windows-1252", Windows1252),
+        ("cp1252", Windows1252),
+        ("latin1", Windows1252),
+        ("ISO-8859-2", Encoding::Latin2),
+        ("cp1250", Encoding::Windows1250),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(assert_ok!(input.parse::<Encoding>()), expected, "{input}");
+    }
+
+    let err = assert_err!("utf-16".parse::<Encoding>());
+    assert_snapshot!(err, @"Unknown encoding: 'utf-16' (expected utf-8, windows-1252, iso-8859-2 or windows-1250)");
+}
+
+#[test]
+fn test_encoding_display_roundtrip() {
+    for encoding in [Utf8, Windows1252, Encoding::Latin2, Encoding::Windows1250] {
+        let parsed = assert_ok!(encoding.to_string().parse::<Encoding>());
+        assert_eq!(parsed, encoding);
+    }
+    assert_eq!(Utf8.to_string(), "UTF-8");
+}
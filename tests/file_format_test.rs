@@ -151,6 +151,66 @@ fn test_related_tasks_separator() {
     assert_eq!(cup.tasks[0].description, None);
 }
 
+#[test]
+fn test_whitespace_only_task_description() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Waypoint","W",XX,5147.809N,00405.003W,500m,1
+-----Related Tasks-----
+"   ","Waypoint","Waypoint"
+" Task ","Waypoint","Waypoint"
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.tasks.len(), 2);
+    assert_eq!(cup.tasks[0].description, None);
+    assert_eq!(cup.tasks[0].description_or_default(), "");
+    assert_eq!(cup.tasks[1].description.as_deref(), Some(" Task "));
+    assert_eq!(cup.tasks[1].description_or_default(), " Task ");
+}
+
+#[test]
+fn test_related_tasks_separator_variants() {
+    for separator in [
+        "----- Related Tasks -----",
+        "---Related Tasks---",
+        "  -----related tasks-----   ",
+        "-----RELATED TASKS----------",
+    ] {
+        let input = format!(
+            r#"name,code,country,lat,lon,elev,style
+"Waypoint","W",XX,5147.809N,00405.003W,500m,1
+{separator}
+,"Waypoint","Waypoint"
+"#
+        );
+
+        let (cup, warnings) = assert_ok!(CupFile::from_str(&input));
+        assert_eq!(warnings.len(), 0, "{separator}");
+        assert_eq!(cup.waypoints.len(), 1, "{separator}");
+        assert_eq!(cup.tasks.len(), 1, "{separator}");
+    }
+}
+
+#[test]
+fn test_invalid_related_tasks_separators() {
+    for separator in [
+        "--Related Tasks--",
+        "-----Related-----",
+        "-----Related Tasks",
+    ] {
+        let input = format!(
+            r#"name,code,country,lat,lon,elev,style
+"Waypoint","W",XX,5147.809N,00405.003W,500m,1
+{separator}
+"#
+        );
+
+        let (cup, warnings) = assert_ok!(CupFile::from_str(&input));
+        assert_eq!(warnings.len(), 1, "{separator}");
+        assert_eq!(cup.waypoints.len(), 1, "{separator}");
+    }
+}
+
 #[test]
 fn test_arbitrary_column_order_with_all_fields() {
     let input = r#"desc,style,elev,lon,lat,country,code,name,freq,rwdir,rwlen,rwwidth
@@ -164,3 +224,31 @@ fn test_arbitrary_column_order_with_all_fields() {
     assert_eq!(cup.waypoints[0].style, WaypointStyle::SolidAirfield);
     assert_eq!(&cup.waypoints[0].description, "Airport desc");
 }
+
+#[test]
+fn test_task_section_without_waypoints() {
+    let input = r#"-----Related Tasks-----
+"Inline Task","Start","TP","Finish"
+Options,TaskTime=02:00:00
+Point=0,"Start",S,XX,4500.000N,01000.000E,500m,2,,,,,,
+Point=1,"TP",T,XX,4600.000N,01000.000E,600m,1,,,,,,
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints.len(), 0);
+    assert_eq!(cup.tasks.len(), 1);
+
+    let task = &cup.tasks[0];
+    assert_some_eq!(&task.description, "Inline Task");
+    assert_eq!(task.waypoint_names, ["Start", "TP", "Finish"]);
+    assert_eq!(task.points.len(), 2);
+    assert_eq!(task.points[0].1.name, "Start");
+    assert_eq!(task.points[1].1.style, WaypointStyle::Waypoint);
+    assert_eq!(task.points[1].1.latitude, 46.0);
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message(),
+        "Unresolved task point: 'Finish' (no waypoint section)"
+    );
+}
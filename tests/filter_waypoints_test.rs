@@ -0,0 +1,184 @@
+use claims::{assert_err, assert_none, assert_ok};
+use seeyou_cup::{CupFile, TaskRetention};
+
+#[test]
+fn test_filter_waypoints() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Aachen",AACHEN,DE,5049.383N,00611.183E,189m,5
+"Eschweiler",ESCHW,DE,5048.000N,00616.000E,500ft,1
+"Düren",DUEREN,DE,5048.000N,00629.000E,150m,3
+-----Related Tasks-----
+"Airfields","Aachen","Düren","Aachen"
+"Via Eschweiler","Aachen","Eschweiler","Aachen"
+"Inline","Aachen","Eschweiler","Aachen"
+Point=1,"Eschweiler",ESCHW,DE,5048.000N,00616.000E,500ft,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let filtered =
+        cup.filter_waypoints(|wp| wp.style.is_landable(), TaskRetention::DropReferencing);
+
+    let names = filtered.waypoints.iter().map(|wp| wp.name.as_str());
+    assert_eq!(names.collect::<Vec<_>>(), vec!["Aachen", "Düren"]);
+
+    let tasks = filtered.tasks.iter().map(|t| t.description.as_deref());
+    assert_eq!(
+        tasks.collect::<Vec<_>>(),
+        vec![Some("Airfields"), Some("Inline")]
+    );
+
+    // The original file is left unchanged
+    assert_eq!(cup.waypoints.len(), 3);
+    assert_eq!(cup.tasks.len(), 3);
+
+    let filtered = cup.filter_waypoints(|wp| wp.style.is_landable(), TaskRetention::KeepAll);
+    assert_eq!(filtered.waypoints.len(), 2);
+    assert_eq!(filtered.tasks, cup.tasks);
+}
+
+#[test]
+fn test_filter_waypoints_multiple_starts() {
+    let (cup, _) = assert_ok!(CupFile::from_str(REFERENCES_FILE));
+
+    // "Stolberg" is only referenced as one of the multiple starts
+    let filtered = cup.filter_waypoints(|wp| wp.name != "Stolberg", TaskRetention::DropReferencing);
+
+    let tasks = filtered.tasks.iter().map(|t| t.description.as_deref());
+    assert_eq!(tasks.collect::<Vec<_>>(), vec![Some("Inline")]);
+}
+
+const REFERENCES_FILE: &str = r#"name,code,country,lat,lon,elev,style
+"Aachen",AACHEN,DE,5049.383N,00611.183E,189m,5
+"Eschweiler",ESCHW,DE,5048.000N,00616.000E,500ft,1
+"Düren",DUEREN,DE,5048.000N,00629.000E,150m,3
+"Stolberg",STOLB,DE,5046.000N,00614.000E,250m,1
+-----Related Tasks-----
+"Airfields","Aachen","Düren","Aachen"
+STARTS="Aachen","Stolberg"
+"Inline","Aachen","Eschweiler","Aachen"
+Point=1,"Eschweiler",ESCHW,DE,5048.000N,00616.000E,500ft,1
+"#;
+
+#[test]
+fn test_tasks_referencing() {
+    let (cup, _) = assert_ok!(CupFile::from_str(REFERENCES_FILE));
+
+    assert_eq!(cup.tasks_referencing("Aachen"), [0, 1]);
+    assert_eq!(cup.tasks_referencing("Düren"), [0]);
+    assert_eq!(cup.tasks_referencing("Stolberg"), [0]);
+    assert_eq!(cup.tasks_referencing("Eschweiler"), [] as [usize; 0]);
+    assert_eq!(cup.tasks_referencing("Unknown"), [] as [usize; 0]);
+}
+
+#[test]
+fn test_remove_waypoint() {
+    let (mut cup, _) = assert_ok!(CupFile::from_str(REFERENCES_FILE));
+
+    // Only referenced by an inline task point
+    assert_ok!(cup.remove_waypoint("Eschweiler"));
+    let names = cup.waypoints.iter().map(|wp| wp.name.as_str());
+    assert_eq!(names.collect::<Vec<_>>(), ["Aachen", "Düren", "Stolberg"]);
+
+    let error = assert_err!(cup.remove_waypoint("Aachen"));
+    insta::assert_snapshot!(error, @"Reference error: Waypoint 'Aachen' is referenced by tasks 0, 1");
+    let error = assert_err!(cup.remove_waypoint("Stolberg"));
+    insta::assert_snapshot!(error, @"Reference error: Waypoint 'Stolberg' is referenced by tasks 0");
+    let error = assert_err!(cup.remove_waypoint("Eschweiler"));
+    insta::assert_snapshot!(error, @"Reference error: Unknown waypoint: 'Eschweiler'");
+    assert!(error.is_recoverable());
+    assert_none!(error.line());
+    assert_eq!(cup.waypoints.len(), 3);
+    assert_eq!(cup.tasks.len(), 2);
+}
+
+#[test]
+fn test_split_by_country() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Aachen",AACHEN,DE,5049.383N,00611.183E,189m,5
+"Düren",DUEREN,DE,5048.000N,00629.000E,150m,3
+"Maastricht",EHBK,NL,5054.917N,00546.217E,114m,5
+"Somewhere",SOME,,5050.000N,00600.000E,200m,1
+-----Related Tasks-----
+"German","Aachen","Düren","Aachen"
+"Cross-border","Aachen","Maastricht","Aachen"
+"Inline","Maastricht","Vaals","Maastricht"
+Point=1,"Vaals",VAALS,NL,5046.000N,00601.000E,200m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let files = cup.split_by_country();
+
+    let dropped = cup.cross_country_tasks();
+    assert_eq!(dropped, [1]);
+    assert_eq!(cup.tasks[1].description.as_deref(), Some("Cross-border"));
+
+    let mut countries = files.keys().map(String::as_str).collect::<Vec<_>>();
+    countries.sort();
+    assert_eq!(countries, ["", "DE", "NL"]);
+
+    let summary = |country: &str| {
+        let file = &files[country];
+        let waypoints = file.waypoints.iter().map(|wp| wp.name.as_str());
+        let tasks = file.tasks.iter().map(|t| t.description.as_deref().unwrap());
+        (waypoints.collect::<Vec<_>>(), tasks.collect::<Vec<_>>())
+    };
+
+    assert_eq!(summary("DE"), (vec!["Aachen", "Düren"], vec!["German"]));
+    assert_eq!(summary("NL"), (vec!["Maastricht"], vec!["Inline"]));
+    assert_eq!(summary(""), (vec!["Somewhere"], vec![]));
+}
+
+/// Output of the multi-task writer test
+const MULTI_TASK_FILE: &str = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+Start A,SA,XX,4500.000N,01000.000E,500.0m,2,,,,,,,
+Turn Point,TP,XX,4600.000N,01100.000E,600.0m,1,,,,,,,
+Finish B,FB,XX,4700.000N,01200.000E,700.0m,5,,,,,,,
+-----Related Tasks-----
+Triangle Task,Start A,Turn Point,Start A
+Options,NoStart=09:00:00,TaskTime=03:00:00,WpDis=True,MinDis=False
+ObsZone=0,Style=0,R1=1000m,A1=180,Line=False
+Out and Return,Start A,Finish B,Start A
+Options,TaskTime=04:30:00,WpDis=False,NearDis=2km,NearAlt=200.0m,MinDis=True,RandomOrder=False,MaxPts=5,BeforePts=1,AfterPts=1,Bonus=25
+STARTS="Start A","Turn Point"
+,Start A
+ObsZone=1,Style=1,R1=500m,R2=2000m,A2=30,A12=45,Line=True
+Point=2,Inline Goal,IG,XX,4800.000N,01300.000E,800.0m,3,270,800.0m,20.0m,122.5,Emergency landing field,Private field,field1.jpg
+Broken Task,Start A,Unknown,Missing,Unknown
+"#;
+
+#[test]
+fn test_extract_task() {
+    let (cup, _) = assert_ok!(CupFile::from_str(MULTI_TASK_FILE));
+    let names = |file: &CupFile| {
+        let names = file.waypoints.iter().map(|wp| wp.name.clone());
+        names.collect::<Vec<_>>()
+    };
+
+    let extracted = assert_ok!(cup.extract_task(0));
+    assert_eq!(names(&extracted), ["Start A", "Turn Point"]);
+    assert_eq!(extracted.tasks, [cup.tasks[0].clone()]);
+
+    // Multiple starts are referenced as well
+    let extracted = assert_ok!(cup.extract_task(1));
+    assert_eq!(names(&extracted), ["Start A", "Turn Point", "Finish B"]);
+    assert_eq!(extracted.tasks, [cup.tasks[1].clone()]);
+
+    // Inline waypoints stay part of the task
+    let extracted = assert_ok!(cup.extract_task(2));
+    assert_eq!(names(&extracted), ["Start A"]);
+    assert_eq!(extracted.tasks[0].points.len(), 1);
+    let output = assert_ok!(extracted.to_string());
+    insta::assert_snapshot!(output, @r"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Start A,SA,XX,4500.000N,01000.000E,500.0m,2,,,,,,,
+    -----Related Tasks-----
+    ,Start A
+    ObsZone=1,Style=1,R1=500m,R2=2000m,A2=30,A12=45,Line=True
+    Point=2,Inline Goal,IG,XX,4800.000N,01300.000E,800.0m,3,270,800.0m,20.0m,122.5,Emergency landing field,Private field,field1.jpg
+    ");
+
+    let error = assert_err!(cup.extract_task(3));
+    insta::assert_snapshot!(error, @"Reference error: Unresolved waypoints: 'Unknown', 'Missing'");
+    let error = assert_err!(cup.extract_task(4));
+    insta::assert_snapshot!(error, @"Reference error: Unknown task index: 4");
+}
@@ -1,5 +1,5 @@
 use claims::{assert_ok, assert_some_eq};
-use insta::assert_debug_snapshot;
+use insta::{assert_compact_debug_snapshot, assert_debug_snapshot};
 use seeyou_cup::CupFile;
 use std::path::Path;
 
@@ -22,6 +22,30 @@ fn test_parse_task() {
     assert_eq!(task.waypoint_names.len(), 3);
 }
 
+#[test]
+fn test_counts() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Lesce","LJBL",SI,4621.379N,01410.467E,504.0m,5
+-----Related Tasks-----
+"Test Task","Lesce","Lesce"
+"#;
+
+    let (mut cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoint_count(), 1);
+    assert_eq!(cup.task_count(), 1);
+    assert!(!cup.is_empty());
+
+    cup.waypoints.clear();
+    assert!(!cup.is_empty());
+    cup.tasks.clear();
+    assert!(cup.is_empty());
+
+    let cup = CupFile::with_capacity(100, 2);
+    assert!(cup.is_empty());
+    assert!(cup.waypoints.capacity() >= 100);
+    assert!(cup.tasks.capacity() >= 2);
+}
+
 #[test]
 fn test_roundtrip() {
     let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
@@ -64,6 +88,30 @@ fn test_fixture_ec25() {
     assert_eq!(cup.waypoints[0].code, "001ACKRE");
 }
 
+#[test]
+fn test_statistics_ec25() {
+    let path = Path::new("tests/fixtures/EC25.cup");
+    let (cup, _) = assert_ok!(CupFile::from_path(path));
+
+    let statistics = cup.statistics();
+    assert_eq!(statistics.waypoint_count, 221);
+    assert_eq!(statistics.task_count, 0);
+
+    let total: usize = statistics.style_counts.values().sum();
+    assert_eq!(total, statistics.waypoint_count);
+
+    let mut style_counts = statistics.style_counts.into_iter().collect::<Vec<_>>();
+    style_counts.sort_by_key(|(style, _)| style.as_u8());
+    assert_compact_debug_snapshot!(style_counts, @"[(Waypoint, 221)]");
+
+    assert_compact_debug_snapshot!(statistics.countries, @"{}");
+    assert_compact_debug_snapshot!(statistics.elevation_range, @"Some((25.0, 1140.1))");
+
+    let path = Path::new("tests/fixtures/2018_schwarzwald_landefelder.cup");
+    let (cup, _) = assert_ok!(CupFile::from_path(path));
+    assert_compact_debug_snapshot!(cup.statistics().countries, @r#"{"DE", "de"}"#);
+}
+
 #[test]
 fn test_fixture_with_task() {
     let path = Path::new("tests/fixtures/709-km-Dreieck-DMSt-Aachen-Stolberg-TV.cup");
@@ -0,0 +1,92 @@
+#![cfg(feature = "kml")]
+
+use claims::assert_ok;
+use insta::assert_snapshot;
+use seeyou_cup::CupFile;
+
+#[test]
+fn test_to_kml() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+"Aachen",AACHEN,DE,5049.383N,00611.183E,189m,5,,,,,"Main field",,
+"Eschweiler",ESCHW,DE,5048.000N,00616.000E,500ft,1,,,,,,,
+"Düren",DUEREN,DE,5048.000N,00629.000E,150m,5,,,,,,,
+-----Related Tasks-----
+"Triangle","Aachen","Eschweiler","Düren","Aachen"
+"Broken","Aachen","Nowhere"
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let kml = assert_ok!(cup.to_kml());
+    assert_snapshot!(kml, @r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <kml xmlns="http://www.opengis.net/kml/2.2">
+    <Document>
+    <Folder>
+    <name>Solid Airfield</name>
+    <Placemark>
+    <name>Aachen</name>
+    <description>Main field</description>
+    <Point>
+    <coordinates>6.186383333333334,50.82305,189</coordinates>
+    </Point>
+    </Placemark>
+    <Placemark>
+    <name>Düren</name>
+    <Point>
+    <coordinates>6.483333333333333,50.8,150</coordinates>
+    </Point>
+    </Placemark>
+    </Folder>
+    <Folder>
+    <name>Waypoint</name>
+    <Placemark>
+    <name>Eschweiler</name>
+    <Point>
+    <coordinates>6.266666666666667,50.8,152.4</coordinates>
+    </Point>
+    </Placemark>
+    </Folder>
+    <Folder>
+    <name>Tasks</name>
+    <Placemark>
+    <name>Triangle</name>
+    <LineString>
+    <tessellate>1</tessellate>
+    <coordinates>
+    6.186383333333334,50.82305,189
+    6.266666666666667,50.8,152.4
+    6.483333333333333,50.8,150
+    6.186383333333334,50.82305,189
+    </coordinates>
+    </LineString>
+    </Placemark>
+    </Folder>
+    </Document>
+    </kml>
+    "#);
+}
+
+#[test]
+fn test_to_kml_escapes_special_characters() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+"A & B <test>",AB,DE,5049.383N,00611.183E,189m,1,,,,,"Use ""runway"" 'A' > B",,
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let kml = assert_ok!(cup.to_kml());
+    assert!(kml.contains("<name>A &amp; B &lt;test&gt;</name>"));
+    assert!(kml.contains("<description>Use &quot;runway&quot; &apos;A&apos; &gt; B</description>"));
+
+    // Every `&` starts an entity and `<` only appears in tags
+    for (i, _) in kml.match_indices('&') {
+        let entity = &kml[i..kml[i..].find(';').unwrap() + i + 1];
+        assert!(
+            ["&amp;", "&lt;", "&gt;", "&quot;", "&apos;"].contains(&entity),
+            "{entity}"
+        );
+    }
+    for line in kml.lines().skip(1) {
+        let tags = line.matches('<').count();
+        assert!(tags == 0 || (line.starts_with('<') && tags <= 2), "{line}");
+    }
+}
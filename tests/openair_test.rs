@@ -0,0 +1,69 @@
+#![cfg(feature = "openair")]
+
+use claims::{assert_err, assert_ok};
+use insta::assert_snapshot;
+use seeyou_cup::CupFile;
+
+const INPUT: &str = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,4500.000N,01000.000E,500m,2
+"North","N",XX,4600.000N,01000.000E,600m,1
+"East","E",XX,4600.000N,01100.000E,700m,1
+-----Related Tasks-----
+"Test Task","Start","North","East","Start"
+ObsZone=0,Style=2,R1=5km,Line=1
+ObsZone=1,Style=1,R1=500m,A1=180
+ObsZone=2,Style=1,R1=10km,A1=45,R2=500m,A2=180
+ObsZone=3,Style=0,R1=3km,A1=90,A12=270
+"Broken Task","Start","Unknown"
+ObsZone=0,Style=2,R1=5km
+"#;
+
+#[test]
+fn test_export_observation_zones_openair() {
+    let (cup, _) = assert_ok!(CupFile::from_str(INPUT));
+    let openair = assert_ok!(cup.tasks[0].export_observation_zones_openair(&cup));
+    assert_snapshot!(openair, @r"
+    * Test Task
+    AC OTHER
+    AN Start
+    AL GND
+    AH UNL
+    DP 45:00:00 N 010:03:49 E
+    DP 45:00:00 N 009:56:11 E
+    AC OTHER
+    AN North
+    AL GND
+    AH UNL
+    V X=46:00:00 N 010:00:00 E
+    DC 0.270
+    AC OTHER
+    AN East
+    AL GND
+    AH UNL
+    DP 46:00:00 N 011:00:00 E
+    V X=46:00:00 N 011:00:00 E
+    V D=+
+    DB 46:05:08 N 011:02:23 E, 45:58:20 N 011:07:23 E
+    AC OTHER
+    AN East
+    AL GND
+    AH UNL
+    V X=46:00:00 N 011:00:00 E
+    DC 0.270
+    AC OTHER
+    AN Start
+    AL GND
+    AH UNL
+    DP 45:00:00 N 010:00:00 E
+    V X=45:00:00 N 010:00:00 E
+    V D=+
+    DB 44:58:23 N 010:00:00 E, 45:01:37 N 010:00:00 E
+    ");
+}
+
+#[test]
+fn test_export_observation_zones_openair_unresolved() {
+    let (cup, _) = assert_ok!(CupFile::from_str(INPUT));
+    let err = assert_err!(cup.tasks[1].export_observation_zones_openair(&cup));
+    assert_snapshot!(err, @"Reference error: Unresolved task point 1: 'Unknown'");
+}
@@ -0,0 +1,167 @@
+use claims::{assert_err, assert_ok};
+use insta::assert_snapshot;
+use seeyou_cup::{CupFile, Distance, Elevation, ParseOptions, RunwayDimension, Severity};
+
+const INPUT: &str = r#"name,code,country,lat,lon,elev,style,rwdir
+"Good",G,XX,5147.809N,00405.003W,500m,1,
+"Bad Coordinate",B,XX,invalid,00405.003W,500m,1,
+"Bad Runway",R,XX,5147.809N,00405.003W,500m,1,abc
+"#;
+
+#[test]
+fn test_default_options_are_lenient() {
+    let (cup, warnings) = assert_ok!(CupFile::from_reader_with_options(
+        INPUT.as_bytes(),
+        &ParseOptions::default()
+    ));
+    assert_eq!(cup.waypoints.len(), 2);
+    assert_eq!(warnings.len(), 2);
+
+    let severities = warnings.iter().map(|w| w.severity()).collect::<Vec<_>>();
+    assert_eq!(severities, [Severity::Error, Severity::Warning]);
+}
+
+#[test]
+fn test_strict() {
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    let err = assert_err!(CupFile::from_reader_with_options(
+        INPUT.as_bytes(),
+        &options
+    ));
+    assert_snapshot!(err, @"Parse error on line 3: Skipped waypoint: Invalid latitude format: 'invalid' (expected 9 characters, got 7)");
+}
+
+#[test]
+fn test_strict_ignored_field() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir
+"Good",G,XX,5147.809N,00405.003W,500m,1,
+"Bad Runway",R,XX,5147.809N,00405.003W,500m,1,abc
+"#;
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    let err = assert_err!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert_snapshot!(err, @"Parse error on line 3: Ignored field: Invalid runway direction: 'abc'");
+}
+
+#[test]
+fn test_do_not_skip_invalid_waypoints() {
+    let options = ParseOptions {
+        skip_invalid_waypoints: false,
+        ..Default::default()
+    };
+
+    let err = assert_err!(CupFile::from_reader_with_options(
+        INPUT.as_bytes(),
+        &options
+    ));
+    assert_snapshot!(err, @"Parse error on line 3: Invalid latitude format: 'invalid' (expected 9 characters, got 7)");
+}
+
+#[test]
+fn test_collect_errors() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir
+"Good",G,XX,5147.809N,00405.003W,500m,1,
+"Bad Coordinate",B,XX,invalid,00405.003W,500m,1,
+-----Related Tasks-----
+"Task 1","Good","Good"
+Options,NearDis=far
+ObsZone=0,Style=2,R1=400m
+"Task 2","Good","Good"
+ObsZone=1,R1=400m
+"#;
+
+    let err = assert_err!(CupFile::from_str(input));
+    assert_snapshot!(err, @"Parse error on line 6: Invalid distance: 'far'");
+
+    let (cup, issues) = assert_ok!(CupFile::from_reader_collect_errors(input.as_bytes()));
+    assert_eq!(cup.waypoints.len(), 1);
+    assert_eq!(cup.tasks.len(), 2);
+    assert_eq!(cup.tasks[0].options, None);
+    assert_eq!(cup.tasks[0].observation_zones.len(), 1);
+    assert_eq!(cup.tasks[1].observation_zones.len(), 0);
+
+    let messages = issues
+        .iter()
+        .map(|issue| issue.message())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "Skipped waypoint: Invalid latitude format: 'invalid' (expected 9 characters, got 7)",
+            "Invalid distance: 'far'",
+            "Missing ObsZone style",
+        ]
+    );
+    let lines = issues.iter().map(|issue| issue.line()).collect::<Vec<_>>();
+    assert_eq!(lines, [Some(3), Some(6), Some(9)]);
+    assert!(
+        issues
+            .iter()
+            .all(|issue| issue.severity() == Severity::Error)
+    );
+}
+
+#[test]
+fn test_collect_errors_missing_column() {
+    let input = r#"name,code,country,lon,elev,style
+"Test",T,XX,00405.003W,500m,1
+"#;
+
+    let (cup, issues) = assert_ok!(CupFile::from_reader_collect_errors(input.as_bytes()));
+    assert_eq!(cup, CupFile::default());
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].message(), "Missing required column: lat");
+    assert_eq!(issues[0].line(), Some(1));
+}
+
+#[test]
+fn test_accept_comma_decimal() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth
+"Lesce",LJBL,SI,4621.379N,01410.467E,"504,5m",5,144,"1130,5m","30,0m"
+-----Related Tasks-----
+"Task","Lesce","Lesce"
+Options,"NearDis=0,7km","NearAlt=300,5m"
+ObsZone=0,Style=2,"R1=0,5km"
+"#;
+
+    let err = assert_err!(CupFile::from_str(input));
+    assert_snapshot!(err, @"Parse error on line 5: Invalid distance: '0,7km'");
+
+    let options = ParseOptions {
+        accept_comma_decimal: true,
+        ..Default::default()
+    };
+
+    let (cup, warnings) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert_eq!(warnings.len(), 0);
+
+    let waypoint = &cup.waypoints[0];
+    assert_eq!(waypoint.elevation, Elevation::Meters(504.5));
+    assert_eq!(
+        waypoint.runway_length,
+        Some(RunwayDimension::Meters(1130.5))
+    );
+    assert_eq!(waypoint.runway_width, Some(RunwayDimension::Meters(30.0)));
+
+    let task = &cup.tasks[0];
+    let task_options = task.options.as_ref().unwrap();
+    assert_eq!(task_options.near_dis, Some(Distance::Kilometers(0.7)));
+    assert_eq!(task_options.near_alt, Some(Elevation::Meters(300.5)));
+    assert_eq!(
+        task.observation_zones[0].r1,
+        Some(Distance::Kilometers(0.5))
+    );
+}
@@ -0,0 +1,145 @@
+use claims::assert_ok;
+use seeyou_cup::CupFile;
+
+/// Writes the file, parses the output again, and checks that nothing changed
+#[track_caller]
+fn assert_roundtrip(cup: &CupFile) {
+    let output = assert_ok!(cup.to_string());
+    let (parsed, warnings) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(warnings, vec![], "{output}");
+    assert_eq!(&parsed, cup, "{output}");
+}
+
+#[test]
+fn test_fixtures_roundtrip() {
+    // `2018_Hotzenwaldwettbewerb_V3.cup` is excluded, since it contains runway
+    // lengths with more decimals than are written
+    for fixture in [
+        "2018_schwarzwald_landefelder.cup",
+        "709-km-Dreieck-DMSt-Aachen-Stolberg-TV.cup",
+        "EC25.cup",
+    ] {
+        let (cup, _) = assert_ok!(CupFile::from_path(format!("tests/fixtures/{fixture}")));
+        assert_roundtrip(&cup);
+    }
+}
+
+#[test]
+fn test_elevation_text_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style
+Lesce,LJBL,SI,4621.379N,01410.467E,504.0m,5
+Feet,FT,XX,5147.809N,00405.003W,525ft,1
+Sea,SEA,XX,5148.000N,00406.000W,0m,1
+-----Related Tasks-----
+"Task","Lesce","Inline","Lesce"
+Point=1,Inline,IN,XX,5148.500N,00406.500W,504.0m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let output = assert_ok!(cup.to_string());
+
+    // Both the waypoint section and inline task points format elevations
+    // with exactly one decimal, so `504.0m` keeps its trailing zero
+    let elevations = output.lines().skip(1).filter_map(|line| {
+        let fields = line.split(',').collect::<Vec<_>>();
+        let elevation = if line.starts_with("Point=") {
+            fields.get(6)
+        } else {
+            fields.get(5)
+        };
+        elevation.filter(|_| fields.len() > 7).copied()
+    });
+    assert_eq!(
+        elevations.collect::<Vec<_>>(),
+        ["504.0m", "525.0ft", "0.0m", "504.0m"]
+    );
+
+    // Once written, the elevations are stable
+    let (parsed, _) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(assert_ok!(parsed.to_string()), output);
+}
+
+#[test]
+fn test_non_contiguous_point_indices_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,5147.809N,00405.003W,500m,2
+"TP2","T2",XX,5148.200N,00406.200W,620m,1
+"Finish","F",XX,5149.000N,00407.000W,700m,2
+-----Related Tasks-----
+"Task","Start","TP1","TP2","TP3","Finish"
+Point=1,"TP1",T1,XX,5148.000N,00406.000W,600m,1
+Point=3,"TP3",T3,XX,5148.500N,00406.500W,650m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let indices = cup.tasks[0].points.iter().map(|(idx, _)| *idx);
+    assert_eq!(indices.collect::<Vec<_>>(), [1, 3]);
+    assert_roundtrip(&cup);
+}
+
+#[test]
+fn test_unordered_point_indices_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,5147.809N,00405.003W,500m,2
+-----Related Tasks-----
+"Task","Start","TP1","Start","TP3"
+Point=3,"TP3",T3,XX,5148.500N,00406.500W,650m,1
+Point=1,"TP1",T1,XX,5148.000N,00406.000W,600m,1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let indices = cup.tasks[0].points.iter().map(|(idx, _)| *idx);
+    assert_eq!(indices.collect::<Vec<_>>(), [3, 1]);
+    assert_roundtrip(&cup);
+}
+
+#[test]
+fn test_inline_only_task_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,5147.809N,00405.003W,500m,2
+-----Related Tasks-----
+"Inline","Start"
+Point=0,"TP1",T1,XX,5148.000N,00406.000W,600m,1
+Point=1,"TP2",T2,XX,5148.500N,00406.500W,650m,1
+"#;
+
+    let (mut cup, _) = assert_ok!(CupFile::from_str(input));
+    cup.tasks[0].waypoint_names.clear();
+    assert_roundtrip(&cup);
+
+    cup.tasks[0].description = None;
+    cup.tasks.push(cup.tasks[0].clone());
+    assert_roundtrip(&cup);
+}
+
+#[test]
+fn test_runway_direction_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir
+"No Runway",N,XX,5147.809N,00405.003W,500m,5,
+"North",Z,XX,5147.809N,00405.003W,500m,5,000
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints[0].runway_direction, None);
+    assert_eq!(cup.waypoints[1].runway_direction, Some(0));
+
+    let output = assert_ok!(cup.to_string());
+    let lines = output.lines().collect::<Vec<_>>();
+    assert!(lines[1].starts_with("No Runway,N,XX,5147.809N,00405.003W,500.0m,5,,"));
+    assert!(lines[2].starts_with("North,Z,XX,5147.809N,00405.003W,500.0m,5,000,"));
+
+    assert_roundtrip(&cup);
+}
+
+#[test]
+fn test_display() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Aachen",EDKA,DE,5049.383N,00611.183E,189m,5
+-----Related Tasks-----
+"Task","Aachen","Aachen"
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let output = assert_ok!(cup.to_string());
+    assert_eq!(format!("{cup}"), output);
+}
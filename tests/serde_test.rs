@@ -0,0 +1,73 @@
+#![cfg(feature = "serde")]
+
+use claims::assert_ok;
+use insta::assert_snapshot;
+use seeyou_cup::{
+    CupFile, Degrees, Distance, Elevation, HalfAngle, ObservationZone, RunwayDimension,
+    WaypointStyle,
+};
+
+#[test]
+fn test_dimension_serialization() {
+    let json = assert_ok!(serde_json::to_string(&Elevation::Meters(500.0)));
+    assert_snapshot!(json, @r#"{"unit":"m","value":500.0}"#);
+
+    let json = assert_ok!(serde_json::to_string(&Elevation::Feet(1500.0)));
+    assert_snapshot!(json, @r#"{"unit":"ft","value":1500.0}"#);
+
+    let json = assert_ok!(serde_json::to_string(&RunwayDimension::NauticalMiles(1.2)));
+    assert_snapshot!(json, @r#"{"unit":"nm","value":1.2}"#);
+
+    let json = assert_ok!(serde_json::to_string(&Distance::Kilometers(0.7)));
+    assert_snapshot!(json, @r#"{"unit":"km","value":0.7}"#);
+
+    let distance: Distance = assert_ok!(serde_json::from_str(r#"{"unit":"ml","value":3.5}"#));
+    assert_eq!(distance, Distance::StatuteMiles(3.5));
+}
+
+#[test]
+fn test_degrees_serialization() {
+    let json = assert_ok!(serde_json::to_string(&Degrees::new(45.0)));
+    assert_snapshot!(json, @"45.0");
+
+    let degrees: Degrees = assert_ok!(serde_json::from_str("370"));
+    assert_eq!(degrees, Degrees::new(10.0));
+
+    // Half-angles are clamped instead of normalized
+    let json =
+        r#"{"index":0,"style":"Fixed","r1":null,"a1":360.0,"r2":null,"a12":370.0,"line":null}"#;
+    let zone: ObservationZone = assert_ok!(serde_json::from_str(json));
+    assert_eq!(zone.a1, Some(HalfAngle::new(180.0)));
+    assert_eq!(zone.a2, None);
+    assert_eq!(zone.a12, Some(Degrees::new(10.0)));
+}
+
+#[test]
+fn test_waypoint_style_serialization() {
+    let json = assert_ok!(serde_json::to_string(&WaypointStyle::SolidAirfield));
+    assert_snapshot!(json, @r#""solidairfield""#);
+
+    let style: WaypointStyle = assert_ok!(serde_json::from_str(r#""pglandingzone""#));
+    assert_eq!(style, WaypointStyle::PgLandingZone);
+}
+
+#[test]
+fn test_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+"Lesce","LJBL",SI,4621.379N,01410.467E,504.0m,5,144,1130.0m,,123.500,"Home Airfield"
+"Waypoint1","WP1",SI,4622.000N,01411.000E,600ft,1,,,,,"Test waypoint"
+-----Related Tasks-----
+"Test Task","LJBL","WP1","LJBL"
+Options,NoStart=12:34:56,TaskTime=01:45:12,WpDis=False,NearDis=0.7km,NearAlt=300.0m
+ObsZone=0,Style=2,R1=400m,A1=180,Line=1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+
+    let waypoints = assert_ok!(serde_json::to_string(&cup.waypoints));
+    let tasks = assert_ok!(serde_json::to_string(&cup.tasks));
+
+    let waypoints: Vec<_> = assert_ok!(serde_json::from_str(&waypoints));
+    let tasks: Vec<_> = assert_ok!(serde_json::from_str(&tasks));
+    assert_eq!(CupFile { waypoints, tasks }, cup);
+}
@@ -0,0 +1,111 @@
+use claims::{assert_err, assert_ok};
+use insta::assert_snapshot;
+use seeyou_cup::CupFile;
+use std::fs::File;
+use std::io::BufReader;
+
+const FIXTURES: [&str; 4] = [
+    "2018_schwarzwald_landefelder.cup",
+    "2018_Hotzenwaldwettbewerb_V3.cup",
+    "709-km-Dreieck-DMSt-Aachen-Stolberg-TV.cup",
+    "EC25.cup",
+];
+
+#[test]
+fn test_fixtures_match_buffered_parser() {
+    for fixture in FIXTURES {
+        let path = format!("tests/fixtures/{fixture}");
+        let (cup, _) = assert_ok!(CupFile::from_path(&path));
+
+        let reader = BufReader::new(assert_ok!(File::open(&path)));
+        let waypoints = CupFile::waypoints_iter(reader)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|error| panic!("{fixture}: {error}"));
+
+        assert_eq!(waypoints, cup.waypoints, "{fixture}");
+    }
+}
+
+#[test]
+fn test_from_reader_matches_from_path() {
+    for fixture in FIXTURES {
+        let path = format!("tests/fixtures/{fixture}");
+        let (expected, expected_warnings) = assert_ok!(CupFile::from_path(&path));
+
+        let file = assert_ok!(File::open(&path));
+        let (cup, warnings) = assert_ok!(CupFile::from_reader(file));
+        assert_eq!(cup, expected, "{fixture}");
+        assert_eq!(warnings, expected_warnings, "{fixture}");
+    }
+}
+
+#[test]
+fn test_small_chunks() {
+    // Forces the Windows-1252 detection and multi-byte UTF-8 characters
+    // across chunk boundaries
+    for fixture in [
+        "2018_Hotzenwaldwettbewerb_V3.cup",
+        "2018_schwarzwald_landefelder.cup",
+    ] {
+        let path = format!("tests/fixtures/{fixture}");
+        let (cup, _) = assert_ok!(CupFile::from_path(&path));
+
+        let reader = BufReader::with_capacity(3, assert_ok!(File::open(&path)));
+        let waypoints = CupFile::waypoints_iter(reader)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|error| panic!("{fixture}: {error}"));
+
+        assert_eq!(waypoints, cup.waypoints, "{fixture}");
+    }
+}
+
+#[test]
+fn test_stops_at_task_section() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start",S,XX,4500.000N,01000.000E,500m,2
+"Finish",F,XX,4600.000N,01100.000E,600m,5
+-----Related Tasks-----
+"Test Task","Start","Finish"
+"#;
+
+    let names = CupFile::waypoints_iter(input.as_bytes())
+        .map(|result| result.map(|waypoint| waypoint.name))
+        .collect::<Result<Vec<_>, _>>();
+    assert_eq!(assert_ok!(names), ["Start", "Finish"]);
+}
+
+#[test]
+fn test_invalid_waypoints_are_errors() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start",S,XX,4500.000N,01000.000E,500m,2
+"Broken",B,XX,invalid,01000.000E,500m,2
+"Finish",F,XX,4600.000N,01100.000E,600m,5
+"#;
+
+    let results = CupFile::waypoints_iter(input.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert_eq!(assert_ok!(&results[0]).name, "Start");
+    assert_snapshot!(assert_err!(&results[1]), @"Parse error on line 3: Invalid latitude format: 'invalid' (expected 9 characters, got 7)");
+    assert_eq!(assert_ok!(&results[2]).name, "Finish");
+}
+
+#[test]
+fn test_missing_column() {
+    let input = "name,code,country,lat,lon,elev\n";
+
+    let results = CupFile::waypoints_iter(input.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    assert_snapshot!(assert_err!(&results[0]), @"Parse error on line 1: Missing required column: style");
+}
+
+#[test]
+fn test_bom() {
+    let input = "\u{FEFF}name,code,country,lat,lon,elev,style\n\"Zürich\",ZUR,CH,4722.614N,00832.502E,408m,1\n";
+    let results = CupFile::waypoints_iter(input.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(assert_ok!(&results[0]).name, "Zürich");
+
+    let input = b"\xFF\xFEn\0a\0m\0e\0";
+    let results = CupFile::waypoints_iter(&input[..]).collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    assert_snapshot!(assert_err!(&results[0]), @"Encoding error: UTF-16 encoded files are not supported, please convert to UTF-8");
+}
@@ -1,5 +1,9 @@
-use claims::{assert_matches, assert_ok, assert_some, assert_some_eq};
-use seeyou_cup::{CupFile, Distance, Elevation, ObsZoneStyle, WaypointStyle};
+use claims::{assert_err, assert_matches, assert_none, assert_ok, assert_some, assert_some_eq};
+use seeyou_cup::{
+    CupFile, Distance, Elevation, HalfAngle, ObsZoneStyle, ObservationZone, Task, TaskOptions,
+    WaypointStyle,
+};
+use std::time::Duration;
 
 #[test]
 fn test_parse_options_line() {
@@ -23,6 +27,30 @@ Options,NoStart=12:34:56,TaskTime=01:45:12,WpDis=False,NearDis=0.7km,NearAlt=300
     assert_some!(&options.near_alt);
 }
 
+#[test]
+fn test_extra_options_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+WP,W,XX,5147.809N,00405.003W,500.0m,1,,,,,,,
+-----Related Tasks-----
+,WP
+Options,TaskTime=03:00:00,WpDis=True,JumpCnt=2,AATMinTime=02:30:00
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let options = assert_some!(&cup.tasks[0].options);
+    assert_some_eq!(&options.task_time, "03:00:00");
+    assert_eq!(
+        options.extra_options,
+        [
+            ("JumpCnt".to_string(), "2".to_string()),
+            ("AATMinTime".to_string(), "02:30:00".to_string()),
+        ]
+    );
+
+    let output = assert_ok!(cup.to_string());
+    assert_eq!(output, input);
+}
+
 #[test]
 fn test_nostart_time() {
     let input = r#"name,code,country,lat,lon,elev,style
@@ -209,6 +237,82 @@ Options,Bonus=50.5
     assert!((bonus - 50.5).abs() < 0.01);
 }
 
+#[test]
+fn test_validate_points() {
+    let valid = [
+        TaskOptions::default(),
+        TaskOptions::builder().max_pts(5).build(),
+        TaskOptions::builder()
+            .random_order(true)
+            .max_pts(5)
+            .before_pts(2)
+            .after_pts(3)
+            .build(),
+        TaskOptions::builder().before_pts(1).after_pts(1).build(),
+        TaskOptions::builder()
+            .random_order(false)
+            .before_pts(0)
+            .after_pts(0)
+            .build(),
+    ];
+    for options in valid {
+        assert_ok!(options.validate(), "{options:?}");
+    }
+
+    let options = TaskOptions::builder()
+        .random_order(true)
+        .max_pts(4)
+        .before_pts(2)
+        .after_pts(3)
+        .build();
+    let error = assert_err!(options.validate());
+    insta::assert_snapshot!(error, @"BeforePts (2) and AfterPts (3) exceed MaxPts (4)");
+
+    let options = TaskOptions::builder()
+        .max_pts(u32::MAX)
+        .before_pts(u32::MAX)
+        .after_pts(1)
+        .build();
+    assert_err!(options.validate());
+
+    let options = TaskOptions::builder()
+        .random_order(false)
+        .before_pts(1)
+        .build();
+    let error = assert_err!(options.validate());
+    insta::assert_snapshot!(error, @"BeforePts (1) and AfterPts (0) require RandomOrder");
+
+    let options = TaskOptions::builder()
+        .random_order(false)
+        .after_pts(2)
+        .build();
+    assert_err!(options.validate());
+}
+
+#[test]
+fn test_duplicate_options_line() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"WP","W",XX,5147.809N,00405.003W,500m,1
+-----Related Tasks-----
+"Task","WP","WP"
+Options,NoStart=12:00:00,WpDis=True
+Options,TaskTime=02:00:00
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    let options = assert_some!(&cup.tasks[0].options);
+    assert_none!(&options.no_start);
+    assert_none!(options.wp_dis);
+    assert_some_eq!(&options.task_time, "02:00:00");
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message(),
+        "Duplicate Options line, previous options are ignored"
+    );
+    assert_eq!(warnings[0].line(), Some(6));
+}
+
 #[test]
 fn test_parse_obszone_line() {
     let input = r#"name,code,country,lat,lon,elev,style
@@ -228,7 +332,7 @@ ObsZone=1,Style=0,R1=35000m,A1=30,R2=12000m,A2=12,A12=123.4
     assert_eq!(oz0.index, 0);
     assert_eq!(oz0.style, ObsZoneStyle::ToNextPoint);
     assert_matches!(&oz0.r1, Some(Distance::Meters(400.0)));
-    assert_some_eq!(oz0.a1, 180.0);
+    assert_some_eq!(oz0.a1, HalfAngle::new(180.0));
     assert_some_eq!(oz0.line, true);
 }
 
@@ -290,7 +394,7 @@ ObsZone=0,Style=0,R1=500m,A1=90
 
     let (cup, _) = assert_ok!(CupFile::from_str(input));
     let oz = &cup.tasks[0].observation_zones[0];
-    assert_some_eq!(oz.a1, 90.0);
+    assert_some_eq!(oz.a1, HalfAngle::new(90.0));
 }
 
 #[test]
@@ -318,7 +422,7 @@ ObsZone=0,Style=0,R1=500m,A2=45
 
     let (cup, _) = assert_ok!(CupFile::from_str(input));
     let oz = &cup.tasks[0].observation_zones[0];
-    assert_some_eq!(oz.a2, 45.0);
+    assert_some_eq!(oz.a2, HalfAngle::new(45.0));
 }
 
 #[test]
@@ -333,7 +437,7 @@ ObsZone=0,Style=0,R1=500m,A12=123.4
     let (cup, _) = assert_ok!(CupFile::from_str(input));
     let oz = &cup.tasks[0].observation_zones[0];
     let a12 = assert_some!(oz.a12);
-    assert!((a12 - 123.4).abs() < 0.01);
+    assert!((a12.value() - 123.4).abs() < 0.01);
 }
 
 #[test]
@@ -350,6 +454,48 @@ ObsZone=0,Style=0,R1=500m,Line=1
     assert_some_eq!(oz.line, true);
 }
 
+#[test]
+fn test_obszone_line_values() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"WP","W",XX,5147.809N,00405.003W,500m,1
+-----Related Tasks-----
+,"WP","WP","WP","WP","WP","WP"
+ObsZone=0,Style=0,R1=500m,Line=1
+ObsZone=1,Style=0,R1=500m,Line=0
+ObsZone=2,Style=0,R1=500m,Line=TRUE
+ObsZone=3,Style=0,R1=500m,Line=false
+ObsZone=4,Style=0,R1=500m,Line=yes
+ObsZone=5,Style=0,R1=500m
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let lines = cup.tasks[0].observation_zones.iter().map(|oz| oz.line);
+    assert_eq!(
+        lines.collect::<Vec<_>>(),
+        [Some(true), Some(false), Some(true), Some(false), None, None]
+    );
+}
+
+#[test]
+fn test_obszone_line_roundtrip() {
+    for line in [None, Some(true), Some(false)] {
+        let mut task = Task::default();
+        task.push_waypoint("WP");
+        task.observation_zones.push(ObservationZone {
+            line,
+            ..ObservationZone::cylinder(Distance::Meters(500.0))
+        });
+
+        let cup = CupFile {
+            waypoints: vec![],
+            tasks: vec![task],
+        };
+        let output = assert_ok!(cup.to_string());
+        let (parsed, _) = assert_ok!(CupFile::from_str(&output));
+        assert_eq!(parsed.tasks[0].observation_zones[0].line, line, "{output}");
+    }
+}
+
 #[test]
 fn test_parse_starts_line() {
     let input = r#"name,code,country,lat,lon,elev,style
@@ -388,6 +534,44 @@ STARTS=Start1,Start2
     assert_eq!(cup.tasks[0].multiple_starts[1], "Start2");
 }
 
+#[test]
+fn test_multiple_starts_with_commas_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"North, Field","N",XX,5147.809N,00405.003W,500m,2
+"South ""Quoted""","S",XX,5148.000N,00406.000W,600m,2
+-----Related Tasks-----
+,"North, Field"
+STARTS="North, Field","South ""Quoted""",Plain
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let expected = ["North, Field", r#"South "Quoted""#, "Plain"];
+    assert_eq!(cup.tasks[0].multiple_starts, expected);
+
+    let output = assert_ok!(cup.to_string());
+    insta::assert_snapshot!(output.lines().last().unwrap(), @r#"STARTS="North, Field","South ""Quoted""","Plain""#);
+
+    let (cup, _) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(cup.tasks[0].multiple_starts, expected);
+}
+
+#[test]
+fn test_multiple_starts_quoted_first_name() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,5147.809N,00405.003W,500m,2
+-----Related Tasks-----
+,"Start"
+STARTS="North, Field","Start","B"
+,"Start"
+STARTS="South ""Quoted"", Field",Start
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.tasks[0].multiple_starts, ["North, Field", "Start", "B"]);
+    let expected = [r#"South "Quoted", Field"#, "Start"];
+    assert_eq!(cup.tasks[1].multiple_starts, expected);
+}
+
 #[test]
 fn test_inline_waypoint_basic() {
     let input = r#"name,code,country,lat,lon,elev,style
@@ -510,3 +694,81 @@ Point=1,"TP1",T1,XX,5148.000N,00406.000W,600m,1
     assert!((wp1.latitude - wp2.latitude).abs() < 0.0001);
     assert!((wp1.longitude - wp2.longitude).abs() < 0.0001);
 }
+
+#[test]
+fn test_parsed_times() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"WP","W",XX,5147.809N,00405.003W,500m,1
+-----Related Tasks-----
+,"WP"
+Options,NoStart=08:30:00,TaskTime=02:30:45
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let options = assert_some!(&cup.tasks[0].options);
+
+    let no_start = assert_ok!(assert_some!(options.no_start_time()));
+    assert_eq!(no_start.hours, 8);
+    assert_eq!(no_start.minutes, 30);
+    assert_eq!(no_start.seconds, 0);
+    assert_eq!(no_start.to_string(), "08:30:00");
+
+    let task_time = assert_ok!(assert_some!(options.task_duration()));
+    assert_eq!(task_time.as_duration(), Duration::from_secs(9045));
+    assert_eq!(task_time.to_string(), "02:30:45");
+}
+
+#[test]
+fn test_unparseable_times_are_kept() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"WP","W",XX,5147.809N,00405.003W,500m,1
+-----Related Tasks-----
+,"WP"
+Options,NoStart=8:30 AM,TaskTime=01:75:00
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(warnings.len(), 0);
+
+    let options = assert_some!(&cup.tasks[0].options);
+    assert_some_eq!(&options.no_start, "8:30 AM");
+    assert_some_eq!(&options.task_time, "01:75:00");
+
+    let err = assert_err!(assert_some!(options.no_start_time()));
+    insta::assert_snapshot!(err, @"Parse error: Invalid time: '8:30 AM' (expected HH:MM:SS)");
+    let err = assert_err!(assert_some!(options.task_duration()));
+    insta::assert_snapshot!(err, @"Parse error: Time out of range: '01:75:00'");
+}
+
+#[test]
+fn test_missing_times() {
+    let options = TaskOptions::default();
+    assert_none!(options.no_start_time());
+    assert_none!(options.task_duration());
+}
+
+#[test]
+fn test_task_section_errors_have_line_numbers() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,5147.809N,00405.003W,500m,2
+"Finish","F",XX,5149.000N,00407.000W,400m,3
+-----Related Tasks-----
+"Test Task","Start","Finish"
+ObsZone=0,Style=2,R1=abc
+"#;
+
+    let err = assert_err!(CupFile::from_str(input));
+    insta::assert_snapshot!(err, @"Parse error on line 6: Invalid distance: 'abc'");
+
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,5147.809N,00405.003W,500m,2
+-----Related Tasks-----
+"Test Task","Start","Finish"
+Options,NearAlt=high
+Point=x,"Finish","F",XX,5149.000N,00407.000W,400m,3
+"#;
+
+    let (_, issues) = assert_ok!(CupFile::from_reader_collect_errors(input.as_bytes()));
+    let lines = issues.iter().map(|issue| issue.line()).collect::<Vec<_>>();
+    assert_eq!(lines, [Some(5), Some(6)]);
+}
@@ -0,0 +1,366 @@
+use claims::{assert_err, assert_none, assert_ok, assert_some};
+use seeyou_cup::{
+    CupFile, Degrees, Distance, HalfAngle, ObsZoneStyle, ObservationZone, Sector, Task,
+    ZoneDefaults,
+};
+use std::path::Path;
+
+const TASK_FILE: &str = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,4500.000N,01000.000E,500m,2
+"North","N",XX,4600.000N,01000.000E,600m,1
+"East","E",XX,4600.000N,01100.000E,700m,1
+-----Related Tasks-----
+"Test Task","Start","North","East","Start"
+"Broken Task","Start","Unknown"
+"#;
+
+#[test]
+fn test_task_polyline() {
+    let (cup, _) = assert_ok!(CupFile::from_str(TASK_FILE));
+
+    let polyline = assert_ok!(cup.task_polyline(&cup.tasks[0]));
+    assert_eq!(
+        polyline,
+        [(45.0, 10.0), (46.0, 10.0), (46.0, 11.0), (45.0, 10.0)]
+    );
+
+    let error = assert_err!(cup.task_polyline(&cup.tasks[1]));
+    insta::assert_snapshot!(error, @"Reference error: Unresolved task point 1: 'Unknown'");
+}
+
+#[test]
+fn test_task_legs() {
+    let (cup, _) = assert_ok!(CupFile::from_str(TASK_FILE));
+
+    let legs = assert_ok!(cup.task_legs(&cup.tasks[0]));
+    assert_eq!(legs.len(), 3);
+    assert_eq!((legs[0].from, legs[0].to), (0, 1));
+    assert_eq!((legs[2].from, legs[2].to), (2, 3));
+    assert!((legs[0].distance_m - 111_195.0).abs() < 100.0);
+    assert!((legs[1].bearing_deg - 89.6).abs() < 1.0);
+
+    let bearings = assert_some!(cup.tasks[0].leg_bearings(&cup));
+    let leg_bearings = legs.iter().map(|leg| leg.bearing_deg).collect::<Vec<_>>();
+    assert_eq!(leg_bearings, bearings);
+
+    assert_err!(cup.task_legs(&cup.tasks[1]));
+}
+
+#[test]
+fn test_task_legs_fixture() {
+    let path = Path::new("tests/fixtures/709-km-Dreieck-DMSt-Aachen-Stolberg-TV.cup");
+    let (cup, _) = assert_ok!(CupFile::from_path(path));
+    let task = &cup.tasks[0];
+
+    let polyline = assert_ok!(cup.task_polyline(task));
+    assert_eq!(polyline.len(), task.waypoint_names.len());
+
+    let legs = assert_ok!(cup.task_legs(task));
+    assert_eq!(legs.len(), polyline.len() - 1);
+
+    let total: f64 = legs.iter().map(|leg| leg.distance_m).sum();
+    insta::assert_snapshot!(format!("{:.0} km", total / 1000.0), @"709 km");
+}
+
+#[test]
+fn test_leg_bearings() {
+    let (cup, _) = assert_ok!(CupFile::from_str(TASK_FILE));
+
+    let bearings = assert_some!(cup.tasks[0].leg_bearings(&cup));
+    assert_eq!(bearings.len(), 3);
+    assert!(bearings[0].abs() < 1.0 || (bearings[0] - 360.0).abs() < 1.0);
+    assert!((bearings[1] - 89.6).abs() < 1.0);
+    assert!((bearings[2] - 215.7).abs() < 1.0);
+}
+
+#[test]
+fn test_leg_bearings_unresolved() {
+    let (cup, _) = assert_ok!(CupFile::from_str(TASK_FILE));
+    assert_none!(cup.tasks[1].leg_bearings(&cup));
+}
+
+#[test]
+fn test_observation_zone_shapes() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,4500.000N,01000.000E,500m,2
+-----Related Tasks-----
+"Task","Start","Start","Start","Start","Start"
+ObsZone=0,Style=2,R1=5000m,A1=180,Line=1
+ObsZone=1,Style=1,R1=500m,A1=180
+ObsZone=2,Style=1,R1=3000m,A1=45
+ObsZone=3,Style=1,R1=10000m,A1=45,R2=500m,A2=180
+ObsZone=4,Style=3,R1=3000m
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let shapes = cup.tasks[0]
+        .observation_zones
+        .iter()
+        .map(|oz| (oz.is_line(), oz.is_cylinder(), oz.is_sector()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        shapes,
+        [
+            (true, false, false),
+            (false, true, false),
+            (false, false, true),
+            (false, false, true),
+            (false, true, false),
+        ]
+    );
+}
+
+#[test]
+fn test_observation_zone_as_sector() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,4500.000N,01000.000E,500m,2
+-----Related Tasks-----
+"Task","Start","Start","Start","Start"
+ObsZone=0,Style=2,R1=5000m,A1=180,Line=1
+ObsZone=1,Style=1,R1=500m,A1=180
+ObsZone=2,Style=0,R1=3000m,A1=45,A12=270
+ObsZone=3,Style=1,R1=10000m,A1=45,R2=500m,A2=180
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let zones = &cup.tasks[0].observation_zones;
+    assert_none!(zones[0].as_sector());
+    assert_none!(zones[1].as_sector());
+
+    let sector = assert_some!(zones[2].as_sector());
+    assert_eq!(
+        sector,
+        Sector {
+            center_bearing: 270.0,
+            half_angle: 45.0,
+            inner_radius: None,
+            outer_radius: Distance::Meters(3000.0),
+        }
+    );
+
+    let sector = assert_some!(zones[3].as_sector());
+    assert_eq!(sector.center_bearing, 0.0);
+    assert_eq!(sector.inner_radius, Some(Distance::Meters(500.0)));
+    assert_eq!(sector.outer_radius, Distance::Meters(10000.0));
+}
+
+#[test]
+fn test_degrees_normalization() {
+    assert_eq!(Degrees::new(45.0).value(), 45.0);
+    assert_eq!(Degrees::new(370.0).value(), 10.0);
+    assert_eq!(Degrees::new(360.0).value(), 0.0);
+    assert_eq!(Degrees::new(-10.0).value(), 350.0);
+    assert_eq!(Degrees::new(-720.0).value(), 0.0);
+    assert_eq!(Degrees::new(-1e-20).value(), 0.0);
+    assert_eq!(Degrees::from(400.5), Degrees::new(40.5));
+    assert_eq!(f64::from(Degrees::new(-90.0)), 270.0);
+
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,4500.000N,01000.000E,500m,2
+-----Related Tasks-----
+"Test Task","Start","Start"
+ObsZone=0,Style=0,R1=500m,A1=-45,A12=370
+"#;
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    let zone = &cup.tasks[0].observation_zones[0];
+    assert_eq!(zone.a1, Some(HalfAngle::new(0.0)));
+    assert_eq!(zone.a12, Some(Degrees::new(10.0)));
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Clamped A1=-45 to 0", line: Some(5), severity: Warning }), Warning(ParseIssue { message: "Normalized A12=370 to 10", line: Some(5), severity: Info })]"#);
+}
+
+#[test]
+fn test_half_angle_clamping() {
+    assert_eq!(HalfAngle::new(45.0).value(), 45.0);
+    assert_eq!(HalfAngle::new(180.0).value(), 180.0);
+    assert_eq!(HalfAngle::new(360.0).value(), 180.0);
+    assert_eq!(HalfAngle::new(-45.0).value(), 0.0);
+    assert_eq!(HalfAngle::new(-0.0).to_string(), "0");
+    assert_eq!(HalfAngle::from(270.0), HalfAngle::new(180.0));
+    assert_eq!(f64::from(HalfAngle::new(22.5)), 22.5);
+    assert_eq!(
+        assert_ok!("370".parse::<HalfAngle>()),
+        HalfAngle::new(180.0)
+    );
+    assert_err!("north".parse::<HalfAngle>());
+}
+
+#[test]
+fn test_observation_zone_out_of_range_half_angles() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,4500.000N,01000.000E,500m,2
+-----Related Tasks-----
+"Test Task","Start","Start","Start"
+ObsZone=0,Style=1,R1=500m,A1=360
+ObsZone=1,Style=1,R1=500m,A1=-45,A12=90
+ObsZone=2,Style=1,R1=500m,A1=45,R2=100m,A2=400
+"#;
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    let messages = warnings.iter().map(|w| w.message()).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "Clamped A1=360 to 180",
+            "Clamped A1=-45 to 0",
+            "Clamped A2=400 to 180"
+        ]
+    );
+
+    let zones = &cup.tasks[0].observation_zones;
+
+    // A full circle rather than an empty sector
+    assert!(zones[0].is_cylinder());
+    assert!(!zones[0].is_sector());
+    assert_none!(zones[0].as_sector());
+
+    // An empty sector rather than almost a full circle
+    assert!(!zones[1].is_cylinder());
+    let sector = assert_some!(zones[1].as_sector());
+    assert_eq!(sector.half_angle, 0.0);
+    assert_eq!(sector.center_bearing, 90.0);
+
+    assert_eq!(zones[2].a2, Some(HalfAngle::new(180.0)));
+}
+
+#[test]
+fn test_degrees_display() {
+    assert_eq!(Degrees::new(180.0).to_string(), "180");
+    assert_eq!(Degrees::new(22.5).to_string(), "22.5");
+    assert_eq!(Degrees::new(-0.0).to_string(), "0");
+    assert_eq!(Degrees::new(-10.0).to_string(), "350");
+
+    assert_eq!(assert_ok!("12.5".parse::<Degrees>()), Degrees::new(12.5));
+    assert_eq!(assert_ok!(" 365 ".parse::<Degrees>()), Degrees::new(5.0));
+    assert_err!("abc".parse::<Degrees>());
+}
+
+#[test]
+fn test_observation_zone_constructors() {
+    let cylinder = ObservationZone::cylinder(Distance::Meters(500.0));
+    assert_eq!(cylinder.style, ObsZoneStyle::Symmetrical);
+    assert_eq!(cylinder.r1, Some(Distance::Meters(500.0)));
+    assert!(cylinder.is_cylinder());
+    assert!(!cylinder.is_line());
+    assert!(!cylinder.is_sector());
+
+    let line = ObservationZone::line(Distance::Kilometers(2.5));
+    assert_eq!(line.r1, Some(Distance::Kilometers(2.5)));
+    assert!(line.is_line());
+    assert!(!line.is_cylinder());
+    assert!(!line.is_sector());
+}
+
+#[test]
+fn test_obs_zone_style_all() {
+    let styles = ObsZoneStyle::all();
+    assert_eq!(styles.len(), 5);
+    for (value, style) in styles.iter().enumerate() {
+        assert_eq!(ObsZoneStyle::from_u8(value as u8), Some(*style));
+    }
+}
+
+#[test]
+fn test_obs_zone_style_try_from() {
+    assert_eq!(ObsZoneStyle::try_from(0), Ok(ObsZoneStyle::Fixed));
+    assert_eq!(ObsZoneStyle::try_from(4), Ok(ObsZoneStyle::ToStartPoint));
+    assert_eq!(ObsZoneStyle::try_from(5), Err(5));
+    assert_eq!(ObsZoneStyle::try_from(255), Err(255));
+}
+
+#[test]
+fn test_build_task() {
+    let (mut cup, _) = assert_ok!(CupFile::from_str(TASK_FILE));
+    let mut inline = cup.waypoints[1].clone();
+    inline.name = "Inline".to_string();
+    inline.latitude = 45.5;
+
+    let mut task = Task {
+        description: Some("Built".to_string()),
+        ..Default::default()
+    };
+    task.push_waypoint("Start");
+    task.push_inline_point(inline);
+    task.push_waypoint("Start");
+    task.observation_zones.push(ObservationZone {
+        index: 1,
+        ..ObservationZone::cylinder(Distance::Meters(500.0))
+    });
+
+    assert_eq!(task.waypoint_names, vec!["Start", "Inline", "Start"]);
+    assert_eq!(task.points.len(), 1);
+    assert_eq!(task.points[0].0, 1);
+    assert_some!(task.observation_zone_for(1));
+    assert_none!(task.observation_zone_for(0));
+
+    cup.tasks = vec![task];
+    let (parsed, _) = assert_ok!(CupFile::from_str(&assert_ok!(cup.to_string())));
+    assert_eq!(parsed.tasks, cup.tasks);
+    assert_eq!(assert_some!(parsed.tasks[0].leg_bearings(&parsed)).len(), 2);
+}
+
+#[test]
+fn test_effective_r1() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Start","S",XX,4500.000N,01000.000E,500m,2
+"North","N",XX,4600.000N,01000.000E,600m,1
+-----Related Tasks-----
+"Task","Start","North","North","Start"
+ObsZone=0,Style=1
+ObsZone=1,Style=1
+ObsZone=2,Style=1,R1=20km,A1=45
+ObsZone=3,Style=3
+"Finish styles","Start","North","North","Start"
+ObsZone=0,Style=2
+ObsZone=1,Style=2
+ObsZone=2,Style=3
+ObsZone=3,Style=1
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let defaults = ZoneDefaults::default();
+    let radii = |task: &Task| {
+        let zones = task.observation_zones.iter();
+        zones
+            .map(|zone| zone.effective_r1(task, &defaults))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        radii(&cup.tasks[0]),
+        vec![
+            Distance::Kilometers(5.0),
+            Distance::Meters(500.0),
+            Distance::Kilometers(20.0),
+            Distance::Kilometers(3.0),
+        ]
+    );
+
+    // The role depends on the position within the task, not on the style
+    assert_eq!(
+        radii(&cup.tasks[1]),
+        vec![
+            Distance::Kilometers(5.0),
+            Distance::Meters(500.0),
+            Distance::Meters(500.0),
+            Distance::Kilometers(3.0),
+        ]
+    );
+}
+
+#[test]
+fn test_resolve_task() {
+    let (cup, _) = assert_ok!(CupFile::from_str(TASK_FILE));
+
+    let waypoints = assert_ok!(cup.resolve_task(&cup.tasks[0]));
+    let names = waypoints.iter().map(|wp| wp.name.as_str());
+    assert_eq!(
+        names.collect::<Vec<_>>(),
+        vec!["Start", "North", "East", "Start"]
+    );
+
+    let err = assert_err!(cup.resolve_task(&cup.tasks[1]));
+    assert_eq!(
+        err.to_string(),
+        "Reference error: Unresolved task point 1: 'Unknown'"
+    );
+}
@@ -1,6 +1,9 @@
-use claims::{assert_matches, assert_ok};
+use claims::{assert_err, assert_matches, assert_ok};
 use insta::assert_debug_snapshot;
-use seeyou_cup::{CupFile, Elevation, RunwayDimension, WaypointStyle};
+use seeyou_cup::{
+    CupFile, Elevation, HalfAngle, ObsZoneStyle, ParseOptions, RunwayDimension, WaypointStyle,
+};
+use std::path::Path;
 
 #[test]
 fn test_parse_basic_waypoint() {
@@ -28,10 +31,93 @@ fn test_parse_basic_waypoint() {
         description: "",
         userdata: "",
         pictures: [],
+        extra: [],
     }
     "#);
 }
 
+#[test]
+fn test_legacy_freq_desc() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc
+"Cross Hands","CSS",UK,5147.809N,00405.003W,525ft,1,,,,"Turn Point, A48/A476"
+"Lesce","LJBL",SI,4621.379N,01410.467E,504m,5,144,1130m,,123.500
+"Bovec","LJBO",SI,4619.850N,01332.950E,443m,5,,,,"Grass strip","Alpine airfield"
+"#;
+    let options = ParseOptions {
+        legacy_freq_desc: true,
+        ..Default::default()
+    };
+
+    let (cup, _) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert_eq!(cup.waypoints[0].frequency, "");
+    assert_eq!(cup.waypoints[0].description, "Turn Point, A48/A476");
+    assert_eq!(cup.waypoints[1].frequency, "123.500");
+    assert_eq!(cup.waypoints[1].description, "");
+    assert_eq!(cup.waypoints[2].frequency, "Grass strip");
+    assert_eq!(cup.waypoints[2].description, "Alpine airfield");
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints[0].frequency, "Turn Point, A48/A476");
+}
+
+#[test]
+fn test_flexible_coordinates() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Cross Hands","CSS",UK,51:47.809N,004:05.003W,525ft,1
+"Lesce","LJBL",SI,46 21.379N,014 10.467E,504m,5
+"#;
+    let options = ParseOptions {
+        flexible_coordinates: true,
+        ..Default::default()
+    };
+
+    let (cup, warnings) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(cup.waypoints.len(), 2);
+    assert!((cup.waypoints[0].latitude - 51.7968166).abs() < 1e-6);
+    assert!((cup.waypoints[0].longitude + 4.0833833).abs() < 1e-6);
+    assert!((cup.waypoints[1].latitude - 46.3563166).abs() < 1e-6);
+    assert!((cup.waypoints[1].longitude - 14.1744500).abs() < 1e-6);
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints.len(), 0);
+    assert_eq!(warnings.len(), 2);
+}
+
+#[test]
+fn test_flexible_coordinate_decimals() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Short","S",UK,5147.8N,00405.0W,525ft,1
+"Long","L",UK,5147.80901N,00405.00301W,525ft,1
+"#;
+    let options = ParseOptions {
+        flexible_coordinates: true,
+        ..Default::default()
+    };
+
+    let (cup, warnings) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(cup.waypoints.len(), 2);
+    assert!((cup.waypoints[0].latitude - 51.7966666).abs() < 1e-6);
+    assert!((cup.waypoints[0].longitude + 4.0833333).abs() < 1e-6);
+    assert!((cup.waypoints[1].latitude - 51.7968168).abs() < 1e-6);
+    assert!((cup.waypoints[1].longitude + 4.0833835).abs() < 1e-6);
+
+    // Without the option, only the short form is rejected
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints.len(), 1);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '5147.8N' (expected 9 characters, got 7)", line: Some(2), severity: Error })]"#);
+}
+
 #[test]
 fn test_parse_airport() {
     let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
@@ -64,6 +150,7 @@ fn test_parse_airport() {
         description: "Home Airfield",
         userdata: "",
         pictures: [],
+        extra: [],
     }
     "#);
 }
@@ -100,6 +187,7 @@ fn test_parse_outlanding() {
         description: "",
         userdata: "",
         pictures: [],
+        extra: [],
     }
     "#);
 }
@@ -113,7 +201,7 @@ fn test_empty_name_should_error() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Name field cannot be empty", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Name field cannot be empty", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -125,7 +213,7 @@ fn test_invalid_latitude_too_short() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '5147.8N' (expected 9 characters, got 7)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '5147.8N' (expected 9 characters, got 7)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -137,7 +225,7 @@ fn test_invalid_latitude_too_long() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '51247.809N' (unexpected character)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '51247.809N' (unexpected character)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -148,7 +236,7 @@ fn test_invalid_latitude_hemisphere() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '5147.809X' (unexpected character)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid latitude format: '5147.809X' (unexpected character)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -159,7 +247,7 @@ fn test_latitude_out_of_range_positive() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Latitude out of range: '91' (must be between -90 and 90)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Latitude out of range: '91' (must be between -90 and 90)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -170,7 +258,7 @@ fn test_latitude_out_of_range_negative() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Latitude out of range: '-91' (must be between -90 and 90)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Latitude out of range: '-91' (must be between -90 and 90)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -182,7 +270,7 @@ fn test_invalid_longitude_too_short() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid longitude format: '0405.0W' (expected 10 characters, got 7)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid longitude format: '0405.0W' (expected 10 characters, got 7)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -194,7 +282,7 @@ fn test_invalid_longitude_too_long() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid longitude format: '000405.003W' (unexpected character)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid longitude format: '000405.003W' (unexpected character)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -205,7 +293,7 @@ fn test_invalid_longitude_hemisphere() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid longitude format: '00405.003Y' (unexpected character)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid longitude format: '00405.003Y' (unexpected character)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -216,7 +304,7 @@ fn test_longitude_out_of_range_positive() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Longitude out of range: '181' (must be between -180 and 180)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Longitude out of range: '181' (must be between -180 and 180)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -227,7 +315,7 @@ fn test_longitude_out_of_range_negative() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Longitude out of range: '-181' (must be between -180 and 180)", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Longitude out of range: '-181' (must be between -180 and 180)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -318,7 +406,7 @@ fn test_invalid_numeric_elevation() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid elevation unit: 'invalid'", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid elevation: 'invalid'", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -329,7 +417,7 @@ fn test_invalid_elevation_unit() {
     let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints.len(), 0);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Invalid elevation: '500km'", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Skipped waypoint: Unknown elevation unit: 'km' (expected ft or m)", line: Some(2), severity: Error })]"#);
 }
 
 #[test]
@@ -350,21 +438,56 @@ fn test_mixed_elevation_units_in_same_file() {
 #[test]
 fn test_invalid_waypoint_style_defaults_to_unknown() {
     let input = r#"name,code,country,lat,lon,elev,style
-"Test",T,XX,5147.809N,00405.003W,0m,99
+"Test",T,XX,5147.809N,00405.003W,0m,abc
 "#;
 
-    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
     assert_eq!(cup.waypoints[0].style, WaypointStyle::Unknown);
+    assert_eq!(warnings.len(), 1);
 }
 
 #[test]
-fn test_waypoint_style_greater_than_21_defaults_to_unknown() {
+fn test_waypoint_style_with_whitespace() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Test",T,XX,5147.809N,00405.003W,0m," 5 "
+-----Related Tasks-----
+"Task","Test","Test"
+ObsZone= 1,Style= 2 ,R1=400m, A1=45
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(cup.waypoints[0].style, WaypointStyle::SolidAirfield);
+
+    let zone = &cup.tasks[0].observation_zones[0];
+    assert_eq!(zone.index, 1);
+    assert_eq!(zone.style, ObsZoneStyle::ToNextPoint);
+    assert_eq!(zone.a1, Some(HalfAngle::new(45.0)));
+}
+
+#[test]
+fn test_waypoint_style_greater_than_21_is_retained() {
     let input = r#"name,code,country,lat,lon,elev,style
 "Test",T,XX,5147.809N,00405.003W,0m,25
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints[0].style, WaypointStyle::Other(25));
+    assert_eq!(warnings.len(), 0);
+}
+
+#[test]
+fn test_unknown_waypoint_style_roundtrip() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+Test,T,XX,5147.809N,00405.003W,0.0m,99,,,,,,,
 "#;
 
     let (cup, _) = assert_ok!(CupFile::from_str(input));
-    assert_eq!(cup.waypoints[0].style, WaypointStyle::Unknown);
+    assert_eq!(cup.waypoints[0].style, WaypointStyle::Other(99));
+    assert_eq!(cup.waypoints[0].style.as_u8(), 99);
+
+    let output = assert_ok!(cup.to_string());
+    assert_eq!(output, input);
 }
 
 #[test]
@@ -379,7 +502,7 @@ fn test_all_valid_waypoint_styles() {
 
         let (cup, _) = CupFile::from_str(&input).unwrap();
         assert_eq!(cup.waypoints.len(), 1);
-        assert_eq!(cup.waypoints[0].style as u8, style_num);
+        assert_eq!(cup.waypoints[0].style.as_u8(), style_num);
     }
 }
 
@@ -413,6 +536,58 @@ fn test_runway_direction_359() {
     assert_eq!(cup.waypoints[0].runway_direction, Some(359));
 }
 
+#[test]
+fn test_runway_direction_360_is_normalized() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir
+"Test",LJBL,SI,4621.379N,01410.467E,504.0m,5,360
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints[0].runway_direction, Some(0));
+    assert_eq!(warnings.len(), 0);
+}
+
+#[test]
+fn test_runway_direction_out_of_range() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir
+"Test",LJBL,SI,4621.379N,01410.467E,504.0m,5,450
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints[0].runway_direction, None);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Runway direction out of range: '450' (must be between 0 and 360)", line: Some(2), severity: Warning })]"#);
+}
+
+#[test]
+fn test_runway_direction_in_tens() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir
+"A",A,SI,4621.379N,01410.467E,504.0m,5,36
+"B",B,SI,4621.379N,01410.467E,504.0m,5,09
+"C",C,SI,4621.379N,01410.467E,504.0m,5,144
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let directions = cup.waypoints.iter().map(|wp| wp.runway_direction);
+    assert_eq!(
+        directions.collect::<Vec<_>>(),
+        [Some(36), Some(9), Some(144)]
+    );
+
+    let options = ParseOptions {
+        runway_direction_in_tens: true,
+        ..Default::default()
+    };
+    let (cup, _) = assert_ok!(CupFile::from_reader_with_options(
+        input.as_bytes(),
+        &options
+    ));
+    let directions = cup.waypoints.iter().map(|wp| wp.runway_direction);
+    assert_eq!(
+        directions.collect::<Vec<_>>(),
+        [Some(0), Some(90), Some(144)]
+    );
+}
+
 #[test]
 fn test_invalid_numeric_runway_direction() {
     let input = r#"name,code,country,lat,lon,elev,style,rwdir
@@ -422,7 +597,7 @@ fn test_invalid_numeric_runway_direction() {
     assert_eq!(cup.waypoints.len(), 1);
     assert_eq!(cup.waypoints[0].runway_direction, None);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Invalid runway direction: 'abc'", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Invalid runway direction: 'abc'", line: Some(2), severity: Warning })]"#);
 }
 
 #[test]
@@ -473,7 +648,7 @@ fn test_invalid_numeric_runway_length() {
     assert_eq!(cup.waypoints.len(), 1);
     assert_eq!(cup.waypoints[0].runway_length, None);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Invalid runway dimension unit: 'invalid'", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Invalid runway dimension: 'invalid'", line: Some(2), severity: Warning })]"#);
 }
 
 #[test]
@@ -485,7 +660,7 @@ fn test_invalid_runway_dimension_unit() {
     assert_eq!(cup.waypoints.len(), 1);
     assert_eq!(cup.waypoints[0].runway_length, None);
     assert_eq!(warnings.len(), 1);
-    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Invalid runway dimension: '1130km'", line: Some(2) })]"#);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Unknown runway dimension unit: 'km' (expected nm, ml or m)", line: Some(2), severity: Warning })]"#);
 }
 
 #[test]
@@ -508,6 +683,29 @@ fn test_frequency_in_quotes() {
     assert_eq!(&cup.waypoints[0].frequency, "123.500");
 }
 
+#[test]
+fn test_frequency_mhz() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq
+"A",A,SI,4621.379N,01410.467E,504.0m,5,,,,123.500
+"B",B,SI,4621.379N,01410.467E,504.0m,5,,,,123.5
+"C",C,SI,4621.379N,01410.467E,504.0m,5,,,,"123,5"
+"D",D,SI,4621.379N,01410.467E,504.0m,5,,,,
+"E",E,SI,4621.379N,01410.467E,504.0m,5,,,,Tower
+"#;
+
+    let (mut cup, _) = assert_ok!(CupFile::from_str(input));
+    let frequencies = cup.waypoints.iter().map(|wp| wp.frequency_mhz());
+    assert_eq!(
+        frequencies.collect::<Vec<_>>(),
+        vec![Some(123.5), Some(123.5), Some(123.5), None, None]
+    );
+    assert_eq!(cup.waypoints[2].frequency, "123,5");
+
+    cup.waypoints[2].set_frequency_mhz(122.8);
+    assert_eq!(cup.waypoints[2].frequency, "122.800");
+    assert_eq!(cup.waypoints[2].frequency_mhz(), Some(122.8));
+}
+
 #[test]
 fn test_description_unlimited_length() {
     let long_desc = "A".repeat(1000);
@@ -544,3 +742,214 @@ fn test_pictures_in_quotes_when_multiple() {
     let (cup, _) = CupFile::from_str(input).unwrap();
     assert_eq!(cup.waypoints[0].pictures, vec!["pic1.jpg", "pic2.jpg"]);
 }
+
+#[test]
+fn test_pictures_with_escaped_semicolons() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+Test,T,XX,5147.809N,00405.003W,0.0m,1,,,,,,,my\;photo.jpg;back\\slash.jpg;other.jpg
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(
+        cup.waypoints[0].pictures,
+        vec!["my;photo.jpg", r"back\slash.jpg", "other.jpg"]
+    );
+
+    // Backslashes are only escaped where necessary
+    let output = assert_ok!(cup.to_string());
+    assert_eq!(output, input.replace(r"back\\slash", r"back\slash"));
+}
+
+#[test]
+fn test_pictures_with_unescaped_backslash() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+Test,T,XX,5147.809N,00405.003W,0.0m,1,,,,,,,sub\a.jpg;pics\photo.jpg
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(
+        cup.waypoints[0].pictures,
+        vec![r"sub\a.jpg", r"pics\photo.jpg"]
+    );
+
+    let output = assert_ok!(cup.to_string());
+    assert_eq!(output, input);
+}
+
+#[test]
+fn test_pictures_with_ambiguous_backslashes() {
+    let mut cup = assert_ok!(CupFile::from_str(
+        "name,code,country,lat,lon,elev,style\nTest,T,XX,5147.809N,00405.003W,0m,1\n"
+    ))
+    .0;
+    let pictures = vec![r"a\;b.jpg", r"c\\d.jpg", r"dir\", r"e\f.jpg"];
+    cup.waypoints[0].pictures = pictures.iter().map(ToString::to_string).collect();
+
+    let output = assert_ok!(cup.to_string());
+    insta::assert_snapshot!(output.lines().last().unwrap(), @r#"Test,T,XX,5147.809N,00405.003W,0.0m,1,,,,,,,a\\\;b.jpg;c\\\d.jpg;dir\\;e\f.jpg"#);
+
+    let (parsed, _) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(parsed.waypoints[0].pictures, pictures);
+}
+
+#[test]
+fn test_picture_paths() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+"Test",T,XX,5147.809N,00405.003W,0m,1,,,,,,,"a.jpg;sub/b.png;sub\\c.png"
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let base = Path::new("data").join("pics");
+    assert_eq!(
+        cup.waypoints[0].picture_paths(&base),
+        vec![
+            base.join("a.jpg"),
+            base.join("sub").join("b.png"),
+            base.join("sub").join("c.png"),
+        ]
+    );
+}
+
+#[test]
+fn test_picture_paths_outside_of_base() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+"Test",T,XX,5147.809N,00405.003W,0m,1,,,,,,,"../../etc/passwd;/etc/hosts;./a.jpg;sub\..\..\b.jpg;c..d.jpg"
+"#;
+
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+    let base = Path::new("data").join("pics");
+    assert_eq!(
+        cup.waypoints[0].picture_paths(&base),
+        vec![
+            base.join("etc").join("hosts"),
+            base.join("a.jpg"),
+            base.join("c..d.jpg"),
+        ]
+    );
+}
+
+#[test]
+fn test_values_beyond_header_columns() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Trailing Commas",T1,XX,5147.809N,00405.003W,500m,1,,
+"Stray Values",T2,XX,5147.809N,00405.003W,500m,1,,foo,bar
+"#;
+
+    let (cup, warnings) = assert_ok!(CupFile::from_str(input));
+    assert_eq!(cup.waypoints.len(), 2);
+    insta::assert_compact_debug_snapshot!(warnings, @r#"[Warning(ParseIssue { message: "Ignored field: Values beyond the header columns: 'foo', 'bar'", line: Some(3), severity: Warning })]"#);
+}
+
+#[test]
+fn test_extra_columns_preserved() {
+    let input = r#"name,code,qnh,country,lat,lon,elev,style,icao
+"Lesce","LJBL",1013,SI,4621.379N,01410.467E,504.0m,5,LJBL
+"Test",T,,XX,5147.809N,00405.003W,0m,1,
+-----Related Tasks-----
+"Task","Lesce"
+Point=1,"Inline",I,1020,XX,5148.000N,00406.000W,600m,1,EDXX
+"#;
+
+    let (cup, warnings) = CupFile::from_str(input).unwrap();
+    assert_eq!(warnings.len(), 0);
+    assert_eq!(
+        cup.waypoints[0].extra,
+        vec![
+            ("qnh".to_string(), "1013".to_string()),
+            ("icao".to_string(), "LJBL".to_string()),
+        ]
+    );
+    assert_eq!(
+        cup.waypoints[1].extra,
+        vec![
+            ("qnh".to_string(), String::new()),
+            ("icao".to_string(), String::new()),
+        ]
+    );
+
+    let output = cup.to_string().unwrap();
+    insta::assert_snapshot!(output, @r#"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics,qnh,icao
+    Lesce,LJBL,SI,4621.379N,01410.467E,504.0m,5,,,,,,,,1013,LJBL
+    Test,T,XX,5147.809N,00405.003W,0.0m,1,,,,,,,,,
+    -----Related Tasks-----
+    Task,Lesce
+    Point=1,Inline,I,XX,5148.000N,00406.000W,600.0m,1,,,,,,,,1020,EDXX
+    "#);
+
+    let (cup2, _) = CupFile::from_str(&output).unwrap();
+    assert_eq!(cup2, cup);
+}
+
+#[test]
+fn test_elevation_with_and_without_decimal() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth
+"A",A,XX,5147.809N,00405.003W,500m,1,,1130m,30m
+"B",B,XX,5147.809N,00405.003W,500.0m,1,,1130.0m,30.0m
+"#;
+
+    let (cup, warnings) = CupFile::from_str(input).unwrap();
+    assert_eq!(warnings.len(), 0);
+    assert_eq!(cup.waypoints[0].elevation, cup.waypoints[1].elevation);
+    assert_eq!(
+        cup.waypoints[0].runway_length,
+        cup.waypoints[1].runway_length
+    );
+    assert_eq!(cup.waypoints[0].runway_width, cup.waypoints[1].runway_width);
+    assert_eq!(cup.waypoints[0].elevation.to_string(), "500.0m");
+    assert_eq!(Elevation::Feet(504.5).to_string(), "504.5ft");
+    assert_eq!(RunwayDimension::NauticalMiles(1.24).to_string(), "1.2nm");
+}
+
+#[test]
+fn test_elevation_accessors() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Test",T,XX,5147.809N,00405.003W,1000ft,1
+"#;
+
+    let (mut cup, _) = assert_ok!(CupFile::from_str(input));
+    let waypoint = &mut cup.waypoints[0];
+    assert_eq!(waypoint.elevation_feet(), 1000.0);
+    assert!((waypoint.elevation_meters() - 304.8).abs() < 1e-9);
+
+    waypoint.set_elevation_meters(500.0);
+    assert_eq!(waypoint.elevation, Elevation::Meters(500.0));
+    assert_eq!(waypoint.elevation_meters(), 500.0);
+    assert!((waypoint.elevation_feet() - 1640.4199).abs() < 1e-4);
+}
+
+#[test]
+fn test_coordinate_validation() {
+    let input = r#"name,code,country,lat,lon,elev,style
+"Test",T,XX,5147.809N,00405.003W,1000ft,1
+"#;
+
+    let (mut cup, _) = assert_ok!(CupFile::from_str(input));
+    let waypoint = &mut cup.waypoints[0];
+    assert_ok!(waypoint.validate_coordinates());
+
+    for latitude in [-90.0, 0.0, 90.0] {
+        assert_ok!(waypoint.set_latitude(latitude));
+        assert_eq!(waypoint.latitude, latitude);
+    }
+    for longitude in [-180.0, 0.0, 180.0] {
+        assert_ok!(waypoint.set_longitude(longitude));
+        assert_eq!(waypoint.longitude, longitude);
+    }
+
+    let err = assert_err!(waypoint.set_latitude(90.001));
+    assert_eq!(
+        err.message(),
+        "Latitude out of range: '90.001' (must be between -90 and 90)"
+    );
+    assert_err!(waypoint.set_latitude(f64::NAN));
+    let err = assert_err!(waypoint.set_longitude(-180.5));
+    assert_eq!(
+        err.message(),
+        "Longitude out of range: '-180.5' (must be between -180 and 180)"
+    );
+    assert_eq!((waypoint.latitude, waypoint.longitude), (90.0, 180.0));
+
+    waypoint.latitude = -91.0;
+    assert_err!(waypoint.validate_coordinates());
+}
@@ -0,0 +1,183 @@
+use claims::{assert_err, assert_ok};
+use insta::assert_snapshot;
+use seeyou_cup::{WaypointCategory, WaypointStyle};
+use std::collections::HashSet;
+
+#[test]
+fn test_display() {
+    assert_eq!(WaypointStyle::SolidAirfield.to_string(), "Solid Airfield");
+    assert_eq!(
+        WaypointStyle::GlidingAirfield.to_string(),
+        "Gliding Airfield"
+    );
+    assert_eq!(WaypointStyle::Vor.to_string(), "VOR");
+    assert_eq!(WaypointStyle::PgTakeOff.to_string(), "PG Take Off");
+}
+
+#[test]
+fn test_from_str_names() {
+    let style: WaypointStyle = assert_ok!("Solid Airfield".parse());
+    assert_eq!(style, WaypointStyle::SolidAirfield);
+
+    let style: WaypointStyle = assert_ok!("gliding airfield".parse());
+    assert_eq!(style, WaypointStyle::GlidingAirfield);
+
+    let style: WaypointStyle = assert_ok!("NDB".parse());
+    assert_eq!(style, WaypointStyle::Ndb);
+}
+
+#[test]
+fn test_from_str_codes() {
+    let style: WaypointStyle = assert_ok!("0".parse());
+    assert_eq!(style, WaypointStyle::Unknown);
+
+    let style: WaypointStyle = assert_ok!("5".parse());
+    assert_eq!(style, WaypointStyle::SolidAirfield);
+
+    let style: WaypointStyle = assert_ok!("21".parse());
+    assert_eq!(style, WaypointStyle::PgLandingZone);
+}
+
+#[test]
+fn test_from_str_errors() {
+    assert_snapshot!(assert_err!("22".parse::<WaypointStyle>()), @"Invalid waypoint style: '22'");
+    assert_snapshot!(assert_err!("Airport".parse::<WaypointStyle>()), @"Invalid waypoint style: 'Airport'");
+}
+
+#[test]
+fn test_display_roundtrip() {
+    for value in 0..=21 {
+        let style = WaypointStyle::from_u8(value).unwrap();
+        let parsed: WaypointStyle = assert_ok!(style.to_string().parse());
+        assert_eq!(parsed, style);
+    }
+}
+
+#[test]
+fn test_all() {
+    let styles = WaypointStyle::all();
+    assert_eq!(styles.len(), 22);
+    for (value, style) in styles.iter().enumerate() {
+        assert_eq!(style.as_u8() as usize, value);
+    }
+
+    let unique = styles.iter().collect::<HashSet<_>>();
+    assert_eq!(unique.len(), styles.len());
+}
+
+#[test]
+fn test_symbol_key() {
+    assert_eq!(WaypointStyle::SolidAirfield.symbol_key(), "solid_airfield");
+    assert_eq!(WaypointStyle::Vor.symbol_key(), "vor");
+    assert_eq!(WaypointStyle::MountainTop.symbol_key(), "mountain_top");
+    assert_eq!(WaypointStyle::Other(42).symbol_key(), "other");
+
+    let styles = WaypointStyle::all()
+        .iter()
+        .chain([&WaypointStyle::Other(42)]);
+    let keys = styles.map(|style| style.symbol_key()).collect::<Vec<_>>();
+    for key in &keys {
+        assert!(!key.is_empty());
+        assert!(key.bytes().all(|b| b.is_ascii_lowercase() || b == b'_'));
+    }
+
+    let unique = keys.iter().collect::<HashSet<_>>();
+    assert_eq!(unique.len(), keys.len());
+}
+
+#[test]
+fn test_try_from() {
+    assert_eq!(WaypointStyle::try_from(0), Ok(WaypointStyle::Unknown));
+    assert_eq!(WaypointStyle::try_from(5), Ok(WaypointStyle::SolidAirfield));
+    assert_eq!(
+        WaypointStyle::try_from(21),
+        Ok(WaypointStyle::PgLandingZone)
+    );
+    assert_eq!(WaypointStyle::try_from(22), Err(22));
+    assert_eq!(WaypointStyle::try_from(255), Err(255));
+}
+
+#[test]
+fn test_classification() {
+    let landable = (0..=21)
+        .filter_map(WaypointStyle::from_u8)
+        .filter(WaypointStyle::is_landable)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        landable,
+        [
+            WaypointStyle::GrassAirfield,
+            WaypointStyle::Outlanding,
+            WaypointStyle::GlidingAirfield,
+            WaypointStyle::SolidAirfield,
+        ]
+    );
+
+    let airfields = (0..=21)
+        .filter_map(WaypointStyle::from_u8)
+        .filter(WaypointStyle::is_airfield)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        airfields,
+        [
+            WaypointStyle::GrassAirfield,
+            WaypointStyle::GlidingAirfield,
+            WaypointStyle::SolidAirfield,
+        ]
+    );
+
+    let navaids = (0..=21)
+        .filter_map(WaypointStyle::from_u8)
+        .filter(WaypointStyle::is_navaid)
+        .collect::<Vec<_>>();
+    assert_eq!(navaids, [WaypointStyle::Vor, WaypointStyle::Ndb]);
+
+    assert!(!WaypointStyle::Other(99).is_landable());
+}
+
+#[test]
+fn test_category() {
+    use WaypointCategory::*;
+
+    let categories = WaypointStyle::all()
+        .iter()
+        .map(WaypointStyle::category)
+        .collect::<Vec<_>>();
+
+    #[rustfmt::skip]
+    let expected = [
+        Other,    // Unknown
+        Other,    // Waypoint
+        Airfield, // GrassAirfield
+        Airfield, // Outlanding
+        Airfield, // GlidingAirfield
+        Airfield, // SolidAirfield
+        Landmark, // MountainPass
+        Landmark, // MountainTop
+        Landmark, // TransmitterMast
+        Navaid,   // Vor
+        Navaid,   // Ndb
+        Landmark, // CoolingTower
+        Landmark, // Dam
+        Landmark, // Tunnel
+        Landmark, // Bridge
+        Landmark, // PowerPlant
+        Landmark, // Castle
+        Landmark, // Intersection
+        Other,    // Marker
+        Other,    // ControlPoint
+        Other,    // PgTakeOff
+        Landable, // PgLandingZone
+    ];
+    assert_eq!(categories, expected);
+
+    assert_eq!(WaypointStyle::Other(42).category(), Other);
+
+    for style in WaypointStyle::all() {
+        assert_eq!(
+            style.category() == Airfield,
+            style.is_landable(),
+            "{style:?}"
+        );
+    }
+}
@@ -1,8 +1,9 @@
-use claims::{assert_ok, assert_some_eq};
+use claims::{assert_err, assert_ok, assert_some_eq};
 use insta::assert_snapshot;
 use seeyou_cup::{
-    Encoding, CupFile, Distance, Elevation, ObsZoneStyle, ObservationZone, RunwayDimension,
-    Task, TaskOptions, Waypoint, WaypointStyle,
+    CupFile, Degrees, Distance, Elevation, Encoding, HalfAngle, LineEnding, ObsZoneStyle,
+    ObservationZone, ParseOptions, RunwayDimension, Task, TaskOptions, Waypoint, WaypointStyle,
+    WriteOptions,
 };
 use std::io::Cursor;
 
@@ -11,6 +12,7 @@ fn test_write_empty_cup_file() {
     let cup_file = CupFile::default();
     let output = assert_ok!(cup_file.to_string());
     assert_snapshot!(output);
+    assert_eq!(output.trim_end(), seeyou_cup::spec::COLUMNS.join(","));
 }
 
 #[test]
@@ -31,6 +33,7 @@ fn test_write_basic_waypoint() {
         description: "Test description".to_string(),
         userdata: "user data".to_string(),
         pictures: vec!["pic1.jpg".to_string(), "pic2.jpg".to_string()],
+        extra: vec![],
     });
 
     let output = assert_ok!(cup_file.to_string());
@@ -56,6 +59,7 @@ fn test_write_csv_escaping() {
         description: "Description with, comma and \"quotes\"".to_string(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     let output = assert_ok!(cup_file.to_string());
@@ -81,6 +85,7 @@ fn test_write_multiline_fields() {
         description: "Line 1\nLine 2\nLine 3".to_string(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     let output = assert_ok!(cup_file.to_string());
@@ -117,6 +122,7 @@ fn test_coordinate_boundary_values() {
             description: String::new(),
             userdata: String::new(),
             pictures: vec![],
+            extra: vec![],
         });
 
         let output = assert_ok!(cup_file.to_string());
@@ -126,33 +132,8 @@ fn test_coordinate_boundary_values() {
 
 #[test]
 fn test_all_waypoint_styles() {
-    let styles = vec![
-        WaypointStyle::Unknown,
-        WaypointStyle::Waypoint,
-        WaypointStyle::GrassAirfield,
-        WaypointStyle::Outlanding,
-        WaypointStyle::GlidingAirfield,
-        WaypointStyle::SolidAirfield,
-        WaypointStyle::MountainPass,
-        WaypointStyle::MountainTop,
-        WaypointStyle::TransmitterMast,
-        WaypointStyle::Vor,
-        WaypointStyle::Ndb,
-        WaypointStyle::CoolingTower,
-        WaypointStyle::Dam,
-        WaypointStyle::Tunnel,
-        WaypointStyle::Bridge,
-        WaypointStyle::PowerPlant,
-        WaypointStyle::Castle,
-        WaypointStyle::Intersection,
-        WaypointStyle::Marker,
-        WaypointStyle::ControlPoint,
-        WaypointStyle::PgTakeOff,
-        WaypointStyle::PgLandingZone,
-    ];
-
     let mut cup_file = CupFile::default();
-    for style in styles {
+    for &style in WaypointStyle::all() {
         cup_file.waypoints.push(Waypoint {
             name: format!("Style_{:?}", style),
             code: "STY".to_string(),
@@ -168,6 +149,7 @@ fn test_all_waypoint_styles() {
             description: String::new(),
             userdata: String::new(),
             pictures: vec![],
+            extra: vec![],
         });
     }
 
@@ -194,6 +176,7 @@ fn test_task_basic() {
         description: String::new(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     cup_file.waypoints.push(Waypoint {
@@ -211,6 +194,7 @@ fn test_task_basic() {
         description: String::new(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     cup_file.tasks.push(Task {
@@ -245,6 +229,7 @@ fn test_task_with_all_features() {
         description: String::new(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     let inline_waypoint = Waypoint {
@@ -262,32 +247,35 @@ fn test_task_with_all_features() {
         description: "Inline waypoint".to_string(),
         userdata: String::new(),
         pictures: vec!["inline.jpg".to_string()],
+        extra: vec![],
     };
 
     cup_file.tasks.push(Task {
         description: Some("Complex Task".to_string()),
         waypoint_names: vec!["Start".to_string()],
-        options: Some(TaskOptions {
-            no_start: Some("08:30:00".to_string()),
-            task_time: Some("05:00:00".to_string()),
-            wp_dis: Some(true),
-            near_dis: Some(Distance::Kilometers(1.5)),
-            near_alt: Some(Elevation::Meters(300.0)),
-            min_dis: Some(false),
-            random_order: Some(true),
-            max_pts: Some(10),
-            before_pts: Some(2),
-            after_pts: Some(3),
-            bonus: Some(50.5),
-        }),
+        options: Some(
+            TaskOptions::builder()
+                .no_start("08:30:00")
+                .task_time("05:00:00")
+                .wp_dis(true)
+                .near_dis(Distance::Kilometers(1.5))
+                .near_alt(Elevation::Meters(300.0))
+                .min_dis(false)
+                .random_order(true)
+                .max_pts(10)
+                .before_pts(2)
+                .after_pts(3)
+                .bonus(50.5)
+                .build(),
+        ),
         observation_zones: vec![ObservationZone {
             index: 0,
             style: ObsZoneStyle::Fixed,
             r1: Some(Distance::Meters(500.0)),
-            a1: Some(90.0),
+            a1: Some(HalfAngle::new(90.0)),
             r2: Some(Distance::Meters(1000.0)),
-            a2: Some(45.0),
-            a12: Some(123.4),
+            a2: Some(HalfAngle::new(45.0)),
+            a12: Some(Degrees::new(123.4)),
             line: Some(true),
         }],
         points: vec![(1, inline_waypoint)],
@@ -298,6 +286,16 @@ fn test_task_with_all_features() {
         ],
     });
 
+    let names = cup_file.all_waypoints().map(|wp| wp.name.as_str());
+    assert_eq!(names.collect::<Vec<_>>(), ["Start", "Inline TP"]);
+
+    // Iterating over the file only covers the waypoint section
+    let mut names = Vec::new();
+    for waypoint in &cup_file {
+        names.push(waypoint.name.as_str());
+    }
+    assert_eq!(names, ["Start"]);
+
     let output = assert_ok!(cup_file.to_string());
     assert_snapshot!(output);
 }
@@ -322,6 +320,7 @@ fn test_multiple_tasks() {
         description: String::new(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     cup_file.waypoints.push(Waypoint {
@@ -339,6 +338,7 @@ fn test_multiple_tasks() {
         description: String::new(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     cup_file.waypoints.push(Waypoint {
@@ -356,6 +356,7 @@ fn test_multiple_tasks() {
         description: String::new(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     // First task - simple triangle
@@ -366,24 +367,19 @@ fn test_multiple_tasks() {
             "Turn Point".to_string(),
             "Start A".to_string(),
         ],
-        options: Some(TaskOptions {
-            no_start: Some("09:00:00".to_string()),
-            task_time: Some("03:00:00".to_string()),
-            wp_dis: Some(true),
-            near_dis: None,
-            near_alt: None,
-            min_dis: Some(false),
-            random_order: None,
-            max_pts: None,
-            before_pts: None,
-            after_pts: None,
-            bonus: None,
-        }),
+        options: Some(
+            TaskOptions::builder()
+                .no_start("09:00:00")
+                .task_time("03:00:00")
+                .wp_dis(true)
+                .min_dis(false)
+                .build(),
+        ),
         observation_zones: vec![ObservationZone {
             index: 0,
             style: ObsZoneStyle::Fixed,
             r1: Some(Distance::Meters(1000.0)),
-            a1: Some(180.0),
+            a1: Some(HalfAngle::new(180.0)),
             r2: None,
             a2: None,
             a12: None,
@@ -401,19 +397,20 @@ fn test_multiple_tasks() {
             "Finish B".to_string(),
             "Start A".to_string(),
         ],
-        options: Some(TaskOptions {
-            no_start: None,
-            task_time: Some("04:30:00".to_string()),
-            wp_dis: Some(false),
-            near_dis: Some(Distance::Kilometers(2.0)),
-            near_alt: Some(Elevation::Meters(200.0)),
-            min_dis: Some(true),
-            random_order: Some(false),
-            max_pts: Some(5),
-            before_pts: Some(1),
-            after_pts: Some(1),
-            bonus: Some(25.0),
-        }),
+        options: Some(
+            TaskOptions::builder()
+                .task_time("04:30:00")
+                .wp_dis(false)
+                .near_dis(Distance::Kilometers(2.0))
+                .near_alt(Elevation::Meters(200.0))
+                .min_dis(true)
+                .random_order(false)
+                .max_pts(5)
+                .before_pts(1)
+                .after_pts(1)
+                .bonus(25.0)
+                .build(),
+        ),
         observation_zones: vec![],
         points: vec![],
         multiple_starts: vec!["Start A".to_string(), "Turn Point".to_string()],
@@ -435,6 +432,7 @@ fn test_multiple_tasks() {
         description: "Emergency landing field".to_string(),
         userdata: "Private field".to_string(),
         pictures: vec!["field1.jpg".to_string()],
+        extra: vec![],
     };
 
     cup_file.tasks.push(Task {
@@ -447,8 +445,8 @@ fn test_multiple_tasks() {
             r1: Some(Distance::Meters(500.0)),
             a1: None,
             r2: Some(Distance::Meters(2000.0)),
-            a2: Some(30.0),
-            a12: Some(45.0),
+            a2: Some(HalfAngle::new(30.0)),
+            a12: Some(Degrees::new(45.0)),
             line: Some(true),
         }],
         points: vec![(2, inline_waypoint)],
@@ -501,6 +499,7 @@ fn test_encoding_windows1252_roundtrip() {
         description: "Passhöhe".to_string(),
         userdata: String::new(),
         pictures: vec![],
+        extra: vec![],
     });
 
     let mut buffer = Vec::new();
@@ -516,6 +515,17 @@ fn test_encoding_windows1252_roundtrip() {
     assert_eq!(parsed.waypoints[0].name, "Zürich");
     assert_eq!(&parsed.waypoints[0].description, "Passhöhe");
     assert_eq!(warnings.len(), 0);
+
+    let bytes = assert_ok!(cup_file.to_bytes_with_encoding(Encoding::Windows1252));
+    assert_eq!(
+        bytes,
+        parsed
+            .to_bytes_with_encoding(Encoding::Windows1252)
+            .unwrap()
+    );
+    assert!(bytes.starts_with(b"name,"));
+    assert!(bytes.windows(6).any(|w| w == b"Z\xFCrich"));
+    assert!(std::str::from_utf8(&bytes).is_err());
 }
 
 #[test]
@@ -538,6 +548,7 @@ fn test_comprehensive_roundtrip() {
         description: "Multi-line\ndescription with \"quotes\"".to_string(),
         userdata: "User data, with commas".to_string(),
         pictures: vec!["pic1.jpg".to_string(), "pic2.png".to_string()],
+        extra: vec![],
     });
 
     let output = assert_ok!(cup_file.to_string());
@@ -554,3 +565,426 @@ fn test_comprehensive_roundtrip() {
     assert!((wp.latitude - 40.123456).abs() < 0.001);
     assert!((wp.longitude - (-74.987654)).abs() < 0.001);
 }
+
+fn single_waypoint_file() -> CupFile {
+    let mut cup_file = CupFile::default();
+    cup_file.waypoints.push(Waypoint {
+        name: "Start".to_string(),
+        code: "S".to_string(),
+        country: "XX".to_string(),
+        latitude: 45.0,
+        longitude: 10.0,
+        elevation: Elevation::Meters(500.0),
+        style: WaypointStyle::GrassAirfield,
+        runway_direction: None,
+        runway_length: None,
+        runway_width: None,
+        frequency: String::new(),
+        description: String::new(),
+        userdata: String::new(),
+        pictures: vec![],
+        extra: vec![],
+    });
+    cup_file
+}
+
+#[test]
+fn test_single_trailing_newline_waypoints_only() {
+    let cup_file = single_waypoint_file();
+
+    let output = assert_ok!(cup_file.to_string());
+    assert!(output.ends_with(",,,\n"));
+    assert!(!output.ends_with("\n\n"));
+}
+
+#[test]
+fn test_single_trailing_newline_with_tasks() {
+    let mut cup_file = single_waypoint_file();
+    cup_file.tasks.push(Task {
+        description: Some("Test Task".to_string()),
+        waypoint_names: vec!["Start".to_string(), "Start".to_string()],
+        options: None,
+        observation_zones: vec![ObservationZone {
+            index: 0,
+            style: ObsZoneStyle::Symmetrical,
+            r1: Some(Distance::Meters(500.0)),
+            a1: None,
+            r2: None,
+            a2: None,
+            a12: None,
+            line: None,
+        }],
+        points: vec![],
+        multiple_starts: vec![],
+    });
+
+    let output = assert_ok!(cup_file.to_string());
+    assert!(output.ends_with("ObsZone=0,Style=1,R1=500m\n"));
+    assert!(!output.ends_with("\n\n"));
+}
+
+#[test]
+fn test_without_trailing_newline() {
+    let cup_file = single_waypoint_file();
+    let options = WriteOptions {
+        trailing_newline: false,
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::new();
+    assert_ok!(cup_file.to_writer_with_options(&mut buffer, &options));
+    let output = assert_ok!(String::from_utf8(buffer));
+    assert!(output.ends_with(",,,"));
+}
+
+#[test]
+fn test_coordinate_decimals() {
+    let mut cup_file = single_waypoint_file();
+    cup_file.waypoints[0].latitude = 45.123456;
+    cup_file.waypoints[0].longitude = -120.987654;
+
+    let options = WriteOptions {
+        coordinate_decimals: 5,
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::new();
+    assert_ok!(cup_file.to_writer_with_options(&mut buffer, &options));
+    let output = assert_ok!(String::from_utf8(buffer));
+    assert_snapshot!(output, @r"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Start,S,XX,4507.40736N,12059.25924W,500.0m,2,,,,,,,
+    ");
+
+    let (parsed, _) = assert_ok!(CupFile::from_str(&output));
+    assert!((parsed.waypoints[0].latitude - 45.123456).abs() < 0.000001);
+    assert!((parsed.waypoints[0].longitude - (-120.987654)).abs() < 0.000001);
+}
+
+#[test]
+fn test_crlf_line_endings() {
+    let mut cup_file = single_waypoint_file();
+    cup_file.tasks.push(Task {
+        description: Some("Test Task".to_string()),
+        waypoint_names: vec!["Start".to_string(), "Start".to_string()],
+        options: Some(TaskOptions::builder().wp_dis(true).build()),
+        observation_zones: vec![ObservationZone {
+            index: 0,
+            style: ObsZoneStyle::Symmetrical,
+            r1: Some(Distance::Meters(500.0)),
+            a1: None,
+            r2: None,
+            a2: None,
+            a12: None,
+            line: None,
+        }],
+        points: vec![(1, cup_file.waypoints[0].clone())],
+        multiple_starts: vec!["Start".to_string()],
+    });
+
+    let options = WriteOptions {
+        line_ending: LineEnding::Crlf,
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::new();
+    assert_ok!(cup_file.to_writer_with_options(&mut buffer, &options));
+    let output = assert_ok!(String::from_utf8(buffer));
+    assert_eq!(output.matches('\n').count(), 8);
+    assert_eq!(output.matches("\r\n").count(), 8);
+    assert!(output.ends_with("STARTS=\"Start\"\r\n"));
+
+    let (parsed, warnings) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(warnings.len(), 0);
+    assert_eq!(parsed, cup_file);
+}
+
+#[test]
+fn test_header_comment() {
+    let cup_file = single_waypoint_file();
+
+    let options = WriteOptions {
+        header_comment: Some("Generated by seeyou-cup, version 0.3".to_string()),
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::new();
+    assert_ok!(cup_file.to_writer_with_options(&mut buffer, &options));
+    let output = assert_ok!(String::from_utf8(buffer));
+    assert_snapshot!(output, @r#"
+    "Generated by seeyou-cup, version 0.3"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Start,S,XX,4500.000N,01000.000E,500.0m,2,,,,,,,
+    "#);
+
+    // the comment is mistaken for the header row by default
+    assert_err!(CupFile::from_str(&output));
+
+    let options = ParseOptions {
+        skip_leading_lines: 1,
+        ..Default::default()
+    };
+    let (parsed, warnings) = assert_ok!(CupFile::from_reader_with_options(
+        output.as_bytes(),
+        &options
+    ));
+    assert_eq!(warnings.len(), 0);
+    assert_eq!(parsed, cup_file);
+}
+
+#[test]
+fn test_pad_runway_direction() {
+    let mut cup_file = single_waypoint_file();
+    cup_file.waypoints[0].runway_direction = Some(90);
+    let inline = cup_file.waypoints[0].clone();
+    cup_file.tasks.push(Task {
+        description: Some("Task".to_string()),
+        waypoint_names: vec!["Start".to_string(), "Start".to_string()],
+        points: vec![(1, inline)],
+        ..Default::default()
+    });
+
+    let output = assert_ok!(cup_file.to_string());
+    assert_snapshot!(output, @r"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Start,S,XX,4500.000N,01000.000E,500.0m,2,090,,,,,,
+    -----Related Tasks-----
+    Task,Start,Start
+    Point=1,Start,S,XX,4500.000N,01000.000E,500.0m,2,090,,,,,,
+    ");
+
+    let options = WriteOptions {
+        pad_runway_direction: false,
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::new();
+    assert_ok!(cup_file.to_writer_with_options(&mut buffer, &options));
+    let output = assert_ok!(String::from_utf8(buffer));
+    assert_snapshot!(output, @r"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Start,S,XX,4500.000N,01000.000E,500.0m,2,90,,,,,,
+    -----Related Tasks-----
+    Task,Start,Start
+    Point=1,Start,S,XX,4500.000N,01000.000E,500.0m,2,90,,,,,,
+    ");
+
+    let (parsed, _) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(parsed, cup_file);
+}
+
+#[test]
+fn test_append_waypoint_to_writer() {
+    let mut cup_file = CupFile::default();
+    cup_file.waypoints.push(Waypoint {
+        name: "Lesce".to_string(),
+        code: "LJBL".to_string(),
+        country: "SI".to_string(),
+        latitude: 46.3563,
+        longitude: 14.1745,
+        elevation: Elevation::Meters(504.0),
+        style: WaypointStyle::SolidAirfield,
+        runway_direction: None,
+        runway_length: None,
+        runway_width: None,
+        frequency: String::new(),
+        description: String::new(),
+        userdata: String::new(),
+        pictures: vec![],
+        extra: vec![],
+    });
+
+    let mut buffer = assert_ok!(cup_file.to_bytes_with_encoding(Encoding::Utf8));
+
+    let waypoint = Waypoint {
+        name: "Čatež".to_string(),
+        code: String::new(),
+        country: String::new(),
+        latitude: 46.2,
+        longitude: 14.3,
+        elevation: Elevation::Meters(400.0),
+        style: WaypointStyle::Waypoint,
+        runway_direction: None,
+        runway_length: None,
+        runway_width: None,
+        frequency: String::new(),
+        description: String::new(),
+        userdata: String::new(),
+        pictures: vec![],
+        extra: vec![],
+    };
+    assert_ok!(CupFile::append_waypoint_to_writer(
+        &mut buffer,
+        &waypoint,
+        Encoding::Utf8
+    ));
+
+    let output = assert_ok!(String::from_utf8(buffer));
+    assert_snapshot!(output, @r#"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Lesce,LJBL,SI,4621.378N,01410.470E,504.0m,5,,,,,,,
+    Čatež,,,4612.000N,01418.000E,400.0m,1,,,,,,,
+    "#);
+
+    let (parsed, warnings) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(parsed.waypoints.len(), 2);
+    assert_eq!(parsed.waypoints[1].name, "Čatež");
+
+    let mut buffer = Vec::new();
+    assert_err!(CupFile::append_waypoint_to_writer(
+        &mut buffer,
+        &waypoint,
+        Encoding::Windows1252
+    ));
+}
+
+#[test]
+fn test_write_sections_only() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+Lesce,LJBL,SI,4621.379N,01410.467E,504.0m,5,144,1130.0m,,123.500,,,
+Bovec,LJBO,SI,4619.850N,01332.950E,443.0m,2,,,,,,,
+-----Related Tasks-----
+Lesce-Bovec,Lesce,Bovec,Lesce
+ObsZone=0,Style=2,R1=5000m,Line=True
+"#;
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+
+    let mut output = Vec::new();
+    assert_ok!(cup.write_waypoints_only(&mut output, Encoding::Utf8));
+    let output = assert_ok!(String::from_utf8(output));
+    assert_snapshot!(output, @r"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Lesce,LJBL,SI,4621.379N,01410.467E,504.0m,5,144,1130.0m,,123.500,,,
+    Bovec,LJBO,SI,4619.850N,01332.950E,443.0m,2,,,,,,,
+    ");
+
+    let (parsed, warnings) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(parsed.waypoints, cup.waypoints);
+    assert_eq!(parsed.tasks, vec![]);
+
+    let mut output = Vec::new();
+    assert_ok!(cup.write_tasks_only(&mut output, Encoding::Utf8));
+    let output = assert_ok!(String::from_utf8(output));
+    assert_snapshot!(output, @r"
+    -----Related Tasks-----
+    Lesce-Bovec,Lesce,Bovec,Lesce
+    ObsZone=0,Style=2,R1=5000m,Line=True
+    ");
+
+    // The separator is written even without tasks
+    let cup = CupFile::default();
+    let mut output = Vec::new();
+    assert_ok!(cup.write_tasks_only(&mut output, Encoding::Utf8));
+    let output = assert_ok!(String::from_utf8(output));
+    assert_snapshot!(output, @"-----Related Tasks-----");
+    assert!(output.ends_with("-----\n"));
+}
+
+#[test]
+fn test_always_quote_text_fields() {
+    let input = r#"name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+Lesce,LJBL,SI,4621.379N,01410.467E,504.0m,5,144,1130.0m,,123.500,"Home, ""sweet"" home",,lesce.jpg
+Bovec,,SI,4619.850N,01332.950E,443.0m,2,,,,,,,
+-----Related Tasks-----
+Task,Lesce,Inline
+Point=1,Inline,IN,SI,4620.000N,01400.000E,500.0m,1,,,,,,,
+"#;
+    let (cup, _) = assert_ok!(CupFile::from_str(input));
+
+    let output = assert_ok!(cup.to_string());
+    assert_snapshot!(output, @r#"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    Lesce,LJBL,SI,4621.379N,01410.467E,504.0m,5,144,1130.0m,,123.500,"Home, ""sweet"" home",,lesce.jpg
+    Bovec,,SI,4619.850N,01332.950E,443.0m,2,,,,,,,
+    -----Related Tasks-----
+    Task,Lesce,Inline
+    Point=1,Inline,IN,SI,4620.000N,01400.000E,500.0m,1,,,,,,,
+    "#);
+
+    let options = WriteOptions {
+        always_quote_text_fields: true,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    assert_ok!(cup.to_writer_with_options(&mut output, &options));
+    let output = assert_ok!(String::from_utf8(output));
+    assert_snapshot!(output, @r#"
+    name,code,country,lat,lon,elev,style,rwdir,rwlen,rwwidth,freq,desc,userdata,pics
+    "Lesce","LJBL",SI,4621.379N,01410.467E,504.0m,5,144,1130.0m,,123.500,"Home, ""sweet"" home",,"lesce.jpg"
+    "Bovec","",SI,4619.850N,01332.950E,443.0m,2,,,,,"",,""
+    -----Related Tasks-----
+    Task,Lesce,Inline
+    Point=1,"Inline","IN",SI,4620.000N,01400.000E,500.0m,1,,,,,"",,""
+    "#);
+
+    let (parsed, warnings) = assert_ok!(CupFile::from_str(&output));
+    assert_eq!(warnings, vec![]);
+    assert_eq!(parsed, cup);
+}
+
+#[test]
+fn test_write_large_file_streaming() {
+    let mut cup_file = CupFile::default();
+    for idx in 0..50_000 {
+        cup_file.waypoints.push(Waypoint {
+            name: format!("Wendepunkt Müllheim {idx}"),
+            code: format!("WP{idx}"),
+            country: "DE".to_string(),
+            latitude: 47.0 + idx as f64 / 100_000.0,
+            longitude: 7.0 + idx as f64 / 100_000.0,
+            elevation: Elevation::Meters(idx as f64 % 1000.0),
+            style: WaypointStyle::Waypoint,
+            runway_direction: None,
+            runway_length: None,
+            runway_width: None,
+            frequency: String::new(),
+            description: "Kirche, südlicher Ortsrand".to_string(),
+            userdata: String::new(),
+            pictures: vec![],
+            extra: vec![],
+        });
+    }
+
+    let content = assert_ok!(cup_file.to_string());
+    assert_eq!(content.lines().count(), 50_001);
+    assert_eq!(format!("{cup_file}"), content);
+
+    let (expected, _, had_errors) = encoding_rs::WINDOWS_1252.encode(&content);
+    assert!(!had_errors);
+
+    let output = assert_ok!(cup_file.to_bytes_with_encoding(Encoding::Windows1252));
+    assert!(output == *expected);
+}
+
+#[test]
+fn test_to_path_encoding_error_leaves_no_partial_file() {
+    let mut input = "name,code,country,lat,lon,elev,style\n".to_string();
+    for i in 0..1_000 {
+        input.push_str(&format!("WP{i},,SI,4621.378N,01410.470E,504.0m,1\n"));
+    }
+    input.push_str("Čatež,,SI,4612.000N,01418.000E,400.0m,1\n");
+    let (cup_file, _) = assert_ok!(CupFile::from_str(&input));
+
+    let dir = std::env::temp_dir().join(format!("seeyou-cup-{}-to-path", std::process::id()));
+    assert_ok!(std::fs::create_dir_all(&dir));
+    let path = dir.join("output.cup");
+
+    // Čatež can't be encoded, after many rows have already been written
+    assert_err!(cup_file.to_path_with_encoding(&path, Encoding::Windows1252));
+    assert!(!path.exists());
+
+    // An existing file is left untouched
+    assert_ok!(std::fs::write(&path, "previous"));
+    assert_err!(cup_file.to_path_with_encoding(&path, Encoding::Windows1252));
+    assert_eq!(assert_ok!(std::fs::read_to_string(&path)), "previous");
+
+    assert_ok!(cup_file.to_path(&path));
+    let (parsed, _) = assert_ok!(CupFile::from_path(&path));
+    assert_eq!(parsed, cup_file);
+
+    let entries = assert_ok!(std::fs::read_dir(&dir)).count();
+    assert_eq!(entries, 1);
+    assert_ok!(std::fs::remove_dir_all(&dir));
+}